@@ -0,0 +1,101 @@
+use std::{error::Error, sync::Arc};
+
+use zharko::{
+    camera::CameraBuilder,
+    math::{
+        backgrounds::SolidBackground,
+        hittables::{HittableList, Sphere, TriangleMesh},
+        materials::{Dielectric, DiffuseLight, Lambertian, Material},
+        Vec3,
+    },
+    renderers::{MultiRenderer, Renderer, EXR, PPM},
+};
+
+const IMAGE_WIDTH: usize = 400;
+
+/// A quad with the corner `q` and the two edges `u` and `v`, split into two triangles
+fn quad(q: Vec3, u: Vec3, v: Vec3, mat: Arc<dyn Material>) -> Box<TriangleMesh> {
+    Box::new(TriangleMesh::new(
+        vec![q, q + u, q + u + v, q + v],
+        vec![[0, 1, 2], [0, 2, 3]],
+        mat,
+    ))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // A preview and the unclamped radiance for compositing from the same render
+    let mut renderer = MultiRenderer::new()
+        .with(PPM::new("cornell.ppm"))
+        .with(EXR::new("cornell.exr"));
+
+    let camera = CameraBuilder::new()
+        .resolution(IMAGE_WIDTH, IMAGE_WIDTH)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .vfov(40.0)
+        .lookfrom(Vec3::new(278.0, 278.0, -800.0))
+        .lookat(Vec3::new(278.0, 278.0, 0.0))
+        // The box is closed, so the only light comes from the lamp in the ceiling
+        .background(SolidBackground::black())
+        .build()?;
+
+    // Materials
+    let red = Arc::new(Lambertian::new(Vec3::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Vec3::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Vec3::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Vec3::new(15.0, 15.0, 15.0)));
+    let glass = Arc::new(Dielectric::new(1.5));
+
+    // World
+    let mut world = HittableList::new();
+    world.add(quad(
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        green,
+    ));
+    world.add(quad(
+        Vec3::zero(),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        red,
+    ));
+    world.add(quad(
+        Vec3::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+        light,
+    ));
+    world.add(quad(
+        Vec3::zero(),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    ));
+    world.add(quad(
+        Vec3::new(555.0, 555.0, 555.0),
+        Vec3::new(-555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -555.0),
+        white.clone(),
+    ));
+    world.add(quad(
+        Vec3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        white.clone(),
+    ));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(190.0, 90.0, 190.0),
+        90.0,
+        glass,
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(370.0, 120.0, 370.0),
+        120.0,
+        white,
+    )));
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
@@ -1,41 +1,40 @@
 use core::f64;
-use std::rc::Rc;
+use std::{error::Error, sync::Arc};
 
 use rand::Rng;
 use zharko::{
-    camera::Camera,
+    camera::CameraBuilder,
     math::{
-        hittables::{HittableList, Sphere},
+        hittables::{BvhNode, HittableList, Sphere},
         materials::{Dielectric, Lambertian, Metal},
         Vec3,
     },
-    renderers::{Image, PPM},
+    renderers::{Renderer, PNG},
 };
 
 const IMAGE_WIDTH: usize = 1200;
 const ASPECT_RATIO: f64 = 16.0 / 9.0;
 
-fn main() {
-    let renderer = PPM::new();
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PNG::new("cover.png");
 
-    let image_height = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as usize;
-
-    let image = Image::new(IMAGE_WIDTH, image_height);
-    let mut camera = Camera::new(image);
-
-    // Camera settings
-    camera.set_samples_per_pixel(500);
-    camera.set_max_depth(50);
-    camera.set_vfov(20.0);
-    camera.set_camera_pos(Vec3::new(13.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 0.0));
-    camera.set_aperture(0.6, 10.0);
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(500)
+        .max_depth(50)
+        .vfov(20.0)
+        .lookfrom(Vec3::new(13.0, 2.0, 3.0))
+        .lookat(Vec3::new(0.0, 0.0, 0.0))
+        .defocus_angle(0.6)
+        .focus_dist(10.0)
+        .build()?;
 
     // World
     let mut world = HittableList::new();
     let mut rng = rand::rng();
 
     // Ground
-    let ground_material = Rc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    let ground_material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
     world.add(Box::new(Sphere::new(
         Vec3::new(0.0, -1000.0, 0.0),
         1000.0,
@@ -64,7 +63,7 @@ fn main() {
                         rng.random::<f64>(),
                         rng.random::<f64>(),
                     );
-                    let sphere_material = Rc::new(Lambertian::new(albedo));
+                    let sphere_material = Arc::new(Lambertian::new(albedo));
                     world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
                 } else if choose_mat < 0.95 {
                     // Metal
@@ -74,11 +73,11 @@ fn main() {
                         rng.random_range(0.5..1.0),
                     );
                     let fuzz = rng.random_range(0.0..0.5);
-                    let sphere_material = Rc::new(Metal::new(albedo, fuzz));
+                    let sphere_material = Arc::new(Metal::new(albedo, fuzz));
                     world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
                 } else {
                     // Glass
-                    let sphere_material = Rc::new(Dielectric::new(1.5));
+                    let sphere_material = Arc::new(Dielectric::new(1.5));
                     world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
                 }
             }
@@ -86,26 +85,29 @@ fn main() {
     }
 
     // Three large spheres
-    let material1 = Rc::new(Dielectric::new(1.5));
+    let material1 = Arc::new(Dielectric::new(1.5));
     world.add(Box::new(Sphere::new(
         Vec3::new(0.0, 1.0, 0.0),
         1.0,
         material1,
     )));
 
-    let material2 = Rc::new(Lambertian::new(Vec3::new(0.4, 0.2, 0.1)));
+    let material2 = Arc::new(Lambertian::new(Vec3::new(0.4, 0.2, 0.1)));
     world.add(Box::new(Sphere::new(
         Vec3::new(-4.0, 1.0, 0.0),
         1.0,
         material2,
     )));
 
-    let material3 = Rc::new(Metal::new(Vec3::new(0.7, 0.6, 0.5), 0.0));
+    let material3 = Arc::new(Metal::new(Vec3::new(0.7, 0.6, 0.5), 0.0));
     world.add(Box::new(Sphere::new(
         Vec3::new(4.0, 1.0, 0.0),
         1.0,
         material3,
     )));
 
-    camera.render(renderer, &world);
+    let world = BvhNode::new(world);
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
 }
@@ -0,0 +1,68 @@
+use std::{env, error::Error, process, sync::Arc};
+
+use zharko::{
+    camera::CameraBuilder,
+    math::{
+        backgrounds::EnvironmentMap,
+        hittables::{HittableList, Sphere},
+        materials::{Dielectric, Metal},
+        Vec3,
+    },
+    renderers::{Renderer, ToneMapper, ToneMapping, PPM},
+};
+
+const IMAGE_WIDTH: usize = 400;
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+/// Renders a mirror and a glass sphere lit by a panorama:
+/// `cargo run --example environment -- <panorama.hdr|panorama.pfm>`
+fn main() -> Result<(), Box<dyn Error>> {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: environment <panorama.hdr|panorama.pfm>");
+        process::exit(1);
+    };
+    let mut environment = match EnvironmentMap::load(&path) {
+        Ok(environment) => environment,
+        Err(e) => {
+            eprintln!("Cannot load {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+    environment.set_rotation(90.0);
+
+    // The sun in a panorama is far brighter than 1.0, so compress the highlights instead of
+    // clipping them
+    let mut renderer = PPM::new("environment.ppm");
+    renderer.set_tone_mapper(ToneMapper::new(ToneMapping::AcesFilmic));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(60.0)
+        .lookfrom(Vec3::new(0.0, 0.5, 1.5))
+        .lookat(Vec3::new(0.0, 0.0, -1.0))
+        .background(environment)
+        .build()?;
+
+    // Materials
+    let material_mirror = Arc::new(Metal::new(Vec3::new(0.9, 0.9, 0.9), 0.0));
+    let material_glass = Arc::new(Dielectric::new(1.5));
+
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(-0.6, 0.0, -1.0),
+        0.5,
+        material_mirror,
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.6, 0.0, -1.0),
+        0.5,
+        material_glass,
+    )));
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
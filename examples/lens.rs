@@ -0,0 +1,65 @@
+use std::{env, error::Error, sync::Arc};
+
+use zharko::{
+    camera::{CameraBuilder, LensSystem},
+    math::{
+        hittables::{HittableList, Sphere},
+        materials::{Dielectric, Lambertian, Material, Metal},
+        Vec3,
+    },
+    renderers::{Renderer, PPM},
+};
+
+const IMAGE_WIDTH: usize = 400;
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+/// Diagonal of a full-frame 35 mm film in meters
+const FILM_DIAGONAL: f64 = 0.0433;
+
+/// Renders a row of spheres through a multi-element lens focused on the middle one:
+/// `cargo run --example lens -- [prescription]`. Defaults to the double-Gauss 50 mm lens in
+/// `lenses/`.
+fn main() -> Result<(), Box<dyn Error>> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "lenses/dgauss50.txt".to_string());
+    // Prescriptions are in millimeters and the scene in meters
+    let lens = LensSystem::load(&path)?.scaled(0.001);
+
+    let mut renderer = PPM::new("lens.ppm");
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .lookfrom(Vec3::new(0.0, 0.12, 0.6))
+        .lookat(Vec3::new(0.06, 0.0, -0.375))
+        .lens(lens, FILM_DIAGONAL)
+        .focus_dist(1.0)
+        .build()?;
+
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -1000.075, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    )));
+    let materials: [Arc<dyn Material>; 3] = [
+        Arc::new(Lambertian::new(Vec3::new(0.1, 0.2, 0.5))),
+        Arc::new(Metal::new(Vec3::new(0.8, 0.6, 0.2), 0.0)),
+        Arc::new(Dielectric::new(1.5)),
+    ];
+    // Small spheres receding from the camera, the third one lies on the focus plane. Close to
+    // the lens the depth of field is shallow enough to blur the others.
+    for k in 0..7 {
+        world.add(Box::new(Sphere::new(
+            Vec3::new(-0.075 + 0.045 * k as f64, 0.0, -0.075 - 0.15 * k as f64),
+            0.075,
+            materials[k % materials.len()].clone(),
+        )));
+    }
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::{error::Error, sync::Arc};
+
+use zharko::{
+    camera::{CameraBuilder, Projection},
+    math::{
+        hittables::{HittableList, Sphere, TriangleMesh},
+        materials::{Lambertian, Material},
+        Vec3,
+    },
+    renderers::{Renderer, PPM},
+};
+
+const IMAGE_WIDTH: usize = 400;
+const ASPECT_RATIO: f64 = 4.0 / 3.0;
+
+/// An axis-aligned box between the corners `min` and `max`
+fn cuboid(min: Vec3, max: Vec3, mat: Arc<dyn Material>) -> Box<TriangleMesh> {
+    let vertices = (0..8)
+        .map(|k| {
+            Vec3::new(
+                if k & 1 == 0 { min.x } else { max.x },
+                if k & 2 == 0 { min.y } else { max.y },
+                if k & 4 == 0 { min.z } else { max.z },
+            )
+        })
+        .collect();
+    let indices = vec![
+        [0, 2, 3],
+        [0, 3, 1],
+        [4, 5, 7],
+        [4, 7, 6],
+        [0, 1, 5],
+        [0, 5, 4],
+        [2, 6, 7],
+        [2, 7, 3],
+        [0, 4, 6],
+        [0, 6, 2],
+        [1, 3, 7],
+        [1, 7, 5],
+    ];
+    Box::new(TriangleMesh::new(vertices, indices, mat))
+}
+
+/// An isometric view, as used for technical figures. Equally sized objects look equally large no
+/// matter how far away they are.
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PPM::new("orthographic.ppm");
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .projection(Projection::Orthographic { view_height: 5.0 })
+        .lookfrom(Vec3::new(10.0, 10.0, 10.0))
+        .lookat(Vec3::new(0.0, 0.0, 0.0))
+        .build()?;
+
+    // Materials
+    let material_ground = Arc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.8)));
+    let material_box = Arc::new(Lambertian::new(Vec3::new(0.2, 0.4, 0.7)));
+    let material_sphere = Arc::new(Lambertian::new(Vec3::new(0.7, 0.3, 0.3)));
+
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        material_ground,
+    )));
+    for (x, z) in [(-1.5, -1.5), (-1.5, 1.5), (1.5, -1.5), (1.5, 1.5)] {
+        world.add(cuboid(
+            Vec3::new(x - 0.5, 0.0, z - 0.5),
+            Vec3::new(x + 0.5, 1.0, z + 0.5),
+            material_box.clone(),
+        ));
+    }
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, 0.75, 0.0),
+        0.75,
+        material_sphere,
+    )));
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
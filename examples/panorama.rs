@@ -0,0 +1,72 @@
+use std::{error::Error, f64::consts::PI, sync::Arc};
+
+use zharko::{
+    camera::{CameraBuilder, FisheyeMapping, Projection},
+    math::{
+        hittables::{HittableList, Sphere},
+        materials::{Lambertian, Metal},
+        Vec3,
+    },
+    renderers::{Renderer, HDR, PPM},
+};
+
+/// Renders a ring of spheres around the camera as a 360° panorama, which can be used as an
+/// environment map (`panorama.hdr`), and as a 180° fisheye view (`fisheye.ppm`)
+fn main() -> Result<(), Box<dyn Error>> {
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    )));
+    for k in 0..8 {
+        let angle = k as f64 * PI / 4.0;
+        let color = Vec3::new(
+            0.5 + 0.4 * angle.cos(),
+            0.5 + 0.4 * (angle + 2.0).cos(),
+            0.5 + 0.4 * (angle + 4.0).cos(),
+        );
+        let center = Vec3::new(3.0 * angle.sin(), 0.7, -3.0 * angle.cos());
+        if k % 2 == 0 {
+            world.add(Box::new(Sphere::new(
+                center,
+                0.7,
+                Arc::new(Lambertian::new(color)),
+            )));
+        } else {
+            world.add(Box::new(Sphere::new(
+                center,
+                0.7,
+                Arc::new(Metal::new(color, 0.1)),
+            )));
+        }
+    }
+
+    let panorama = CameraBuilder::new()
+        .resolution(800, 400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .projection(Projection::Equirectangular)
+        .lookfrom(Vec3::new(0.0, 1.0, 0.0))
+        .lookat(Vec3::new(0.0, 1.0, -1.0))
+        .build()?;
+    HDR::new("panorama.hdr").draw(&panorama.render(&world))?;
+
+    // Looking straight down at the ring, so `vup` must not be vertical
+    let fisheye = CameraBuilder::new()
+        .resolution(400, 400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .projection(Projection::Fisheye {
+            fov: 180.0,
+            mapping: FisheyeMapping::Equisolid,
+        })
+        .lookfrom(Vec3::new(0.0, 2.5, 0.0))
+        .lookat(Vec3::new(0.0, 0.0, 0.0))
+        .vup(Vec3::new(0.0, 0.0, -1.0))
+        .build()?;
+    PPM::new("fisheye.ppm").draw(&fisheye.render(&world))?;
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::{error::Error, sync::Arc};
+
+use zharko::{
+    camera::CameraBuilder,
+    math::{
+        hittables::{HittableList, Sphere},
+        materials::Lambertian,
+        textures::{Marble, NoiseTexture, Texture, Voronoi, Wood},
+        Vec3,
+    },
+    renderers::{Renderer, PPM},
+};
+
+const IMAGE_WIDTH: usize = 600;
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PPM::new("procedural.ppm");
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(25.0)
+        .lookfrom(Vec3::new(0.0, 2.0, 9.0))
+        .lookat(Vec3::new(0.0, 0.5, 0.0))
+        .build()?;
+
+    // Textures
+    let noise = Arc::new(NoiseTexture::new(1, 4.0, Vec3::new(0.8, 0.8, 0.8)));
+    let marble = Arc::new(Marble::new(2, 4.0, 10.0, Vec3::new(0.9, 0.9, 0.9)));
+    let wood = Arc::new(Wood::new(
+        3,
+        8.0,
+        1.0,
+        Vec3::new(0.8, 0.6, 0.35),
+        Vec3::new(0.35, 0.2, 0.08),
+    ));
+    let voronoi = Arc::new(Voronoi::new(
+        4,
+        4.0,
+        Vec3::new(0.2, 0.5, 0.8),
+        Vec3::new(0.05, 0.05, 0.05),
+    ));
+
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(noise)),
+    )));
+    let textures: [Arc<dyn Texture>; 3] = [marble, wood, voronoi];
+    for (i, texture) in textures.into_iter().enumerate() {
+        world.add(Box::new(Sphere::new(
+            Vec3::new(-2.2 + 2.2 * i as f64, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::from_texture(texture)),
+        )));
+    }
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
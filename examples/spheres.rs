@@ -1,30 +1,29 @@
 use core::f64;
-use std::rc::Rc;
+use std::{error::Error, sync::Arc};
 
 use zharko::{
-    camera::Camera,
+    camera::CameraBuilder,
     math::{
         hittables::{HittableList, Sphere},
         materials::Lambertian,
         Vec3,
     },
-    renderers::{Image, PPM},
+    renderers::{Renderer, PPM},
 };
 
 const IMAGE_WIDTH: usize = 500;
 const ASPECT_RATIO: f64 = 16.0 / 9.0;
 
-fn main() {
-    let renderer = PPM::new();
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PPM::new("spheres.ppm");
 
-    let image_height = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as usize;
-
-    let image = Image::new(IMAGE_WIDTH, image_height);
-    let mut camera = Camera::new(image);
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .build()?;
 
     // Materials - all spheres are diffuse as requested
-    let material_sphere = Rc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
-    let material_ground = Rc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)));
+    let material_sphere = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    let material_ground = Arc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)));
 
     // World
     let mut world = HittableList::new();
@@ -39,5 +38,7 @@ fn main() {
         material_ground,
     )));
 
-    camera.render(renderer, &world);
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
 }
@@ -1,33 +1,32 @@
 use core::f64;
-use std::rc::Rc;
+use std::{error::Error, sync::Arc};
 
 use zharko::{
-    camera::Camera,
+    camera::CameraBuilder,
     math::{
         hittables::{HittableList, Sphere},
         materials::{Dielectric, Lambertian, Metal},
         Vec3,
     },
-    renderers::{Image, PPM},
+    renderers::{Renderer, PPM},
 };
 
 const IMAGE_WIDTH: usize = 800;
 const ASPECT_RATIO: f64 = 16.0 / 9.0;
 
-fn main() {
-    let renderer = PPM::new();
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PPM::new("spheres_2.ppm");
 
-    let image_height = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as usize;
-
-    let image = Image::new(IMAGE_WIDTH, image_height);
-    let mut camera = Camera::new(image);
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .build()?;
 
     // Materials
-    let material_ground = Rc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)));
-    let material_center = Rc::new(Lambertian::new(Vec3::new(0.1, 0.2, 0.5)));
-    let material_left = Rc::new(Dielectric::new(1.5));
-    let material_bubble = Rc::new(Dielectric::new(1.0 / 1.5));
-    let material_right = Rc::new(Metal::new(Vec3::new(0.8, 0.6, 0.2), 1.0));
+    let material_ground = Arc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)));
+    let material_center = Arc::new(Lambertian::new(Vec3::new(0.1, 0.2, 0.5)));
+    let material_left = Arc::new(Dielectric::new(1.5));
+    let material_bubble = Arc::new(Dielectric::new(1.0 / 1.5));
+    let material_right = Arc::new(Metal::new(Vec3::new(0.8, 0.6, 0.2), 1.0));
 
     // World
     let mut world = HittableList::new();
@@ -57,5 +56,7 @@ fn main() {
         material_bubble,
     )));
 
-    camera.render(renderer, &world);
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
 }
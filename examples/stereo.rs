@@ -0,0 +1,68 @@
+use std::{error::Error, sync::Arc};
+
+use zharko::{
+    camera::{CameraBuilder, Projection, StereoBuilder, StereoLayout},
+    math::{
+        hittables::{HittableList, Sphere},
+        materials::{Dielectric, Lambertian, Metal},
+        Vec3,
+    },
+    renderers::{Renderer, PPM},
+};
+
+/// Renders a red/cyan anaglyph (`stereo.ppm`) and an over-under omni-directional stereo panorama
+/// for VR viewers (`stereo_ods.ppm`)
+fn main() -> Result<(), Box<dyn Error>> {
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -100.5, -1.0),
+        100.0,
+        Arc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0))),
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, 0.0, -1.2),
+        0.5,
+        Arc::new(Lambertian::new(Vec3::new(0.1, 0.2, 0.5))),
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(-1.0, 0.0, -1.0),
+        0.5,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(1.0, 0.0, -1.0),
+        0.5,
+        Arc::new(Metal::new(Vec3::new(0.8, 0.6, 0.2), 0.0)),
+    )));
+
+    let camera = CameraBuilder::new()
+        .resolution(400, 225)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(60.0)
+        .lookfrom(Vec3::new(0.0, 0.3, 1.0))
+        .lookat(Vec3::new(0.0, 0.0, -1.0));
+
+    // The spheres are about 2 units away, so they sit just behind the screen
+    let anaglyph = StereoBuilder::new(camera.clone())
+        .interocular_distance(0.1)
+        .convergence_distance(1.5)
+        .layout(StereoLayout::Anaglyph)
+        .build()?;
+    PPM::new("stereo.ppm").draw(&anaglyph.render(&world))?;
+
+    let ods = StereoBuilder::new(
+        camera
+            .resolution(800, 400)
+            .projection(Projection::Equirectangular)
+            // Keep the horizon level
+            .lookat(Vec3::new(0.0, 0.3, -1.0)),
+    )
+    .interocular_distance(0.1)
+    .layout(StereoLayout::OverUnder)
+    .build()?;
+    PPM::new("stereo_ods.ppm").draw(&ods.render(&world))?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::{error::Error, sync::Arc};
+
+use zharko::{
+    camera::CameraBuilder,
+    math::{
+        hittables::{HittableList, Sphere},
+        materials::Lambertian,
+        textures::{Checker, UvChecker},
+        Vec3,
+    },
+    renderers::{Renderer, PPM},
+};
+
+const IMAGE_WIDTH: usize = 400;
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PPM::new("textures.ppm");
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(30.0)
+        .lookfrom(Vec3::new(0.0, 1.0, 3.0))
+        .lookat(Vec3::new(0.0, 0.0, -1.0))
+        .build()?;
+
+    // Textures
+    let checker = Arc::new(Checker::from_colors(
+        0.5,
+        Vec3::new(0.2, 0.3, 0.1),
+        Vec3::new(0.9, 0.9, 0.9),
+    ));
+    let uv_checker = Arc::new(UvChecker::from_colors(
+        16.0,
+        8.0,
+        Vec3::new(0.8, 0.1, 0.1),
+        Vec3::new(0.9, 0.9, 0.9),
+    ));
+
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -100.5, -1.0),
+        100.0,
+        Arc::new(Lambertian::from_texture(checker)),
+    )));
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, 0.0, -1.0),
+        0.5,
+        Arc::new(Lambertian::from_texture(uv_checker)),
+    )));
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
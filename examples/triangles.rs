@@ -0,0 +1,73 @@
+use core::f64;
+use std::{error::Error, sync::Arc};
+
+use zharko::{
+    camera::CameraBuilder,
+    math::{
+        hittables::{HittableList, Sphere, Triangle, TriangleMesh},
+        materials::{Lambertian, Metal},
+        Vec3,
+    },
+    renderers::{Renderer, PPM},
+};
+
+const IMAGE_WIDTH: usize = 400;
+const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut renderer = PPM::new("triangles.ppm");
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(IMAGE_WIDTH, ASPECT_RATIO)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .vfov(40.0)
+        .lookfrom(Vec3::new(2.0, 1.5, 2.0))
+        .lookat(Vec3::new(0.0, 0.3, -1.0))
+        .build()?;
+
+    // Materials
+    let material_ground = Arc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.0)));
+    let material_pyramid = Arc::new(Lambertian::new(Vec3::new(0.7, 0.3, 0.3)));
+    let material_mirror = Arc::new(Metal::new(Vec3::new(0.8, 0.8, 0.8), 0.0));
+
+    // A square pyramid described with shared vertices
+    let vertices = vec![
+        Vec3::new(-0.5, -0.5, -0.5),
+        Vec3::new(0.5, -0.5, -0.5),
+        Vec3::new(0.5, -0.5, -1.5),
+        Vec3::new(-0.5, -0.5, -1.5),
+        Vec3::new(0.0, 0.5, -1.0),
+    ];
+    let indices = vec![
+        [0, 1, 4],
+        [1, 2, 4],
+        [2, 3, 4],
+        [3, 0, 4],
+        [0, 3, 2],
+        [0, 2, 1],
+    ];
+
+    // World
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.0, -100.5, -1.0),
+        100.0,
+        material_ground,
+    )));
+    world.add(Box::new(TriangleMesh::new(
+        vertices,
+        indices,
+        material_pyramid,
+    )));
+    world.add(Box::new(Triangle::new(
+        Vec3::new(-1.5, -0.5, -2.0),
+        Vec3::new(-0.5, -0.5, -2.5),
+        Vec3::new(-1.0, 1.0, -2.25),
+        material_mirror,
+    )));
+
+    let image = camera.render(&world);
+    renderer.draw(&image)?;
+    Ok(())
+}
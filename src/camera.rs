@@ -1,14 +1,431 @@
+pub mod lens;
+pub mod projection;
+pub mod stereo;
+
+pub use lens::{LensElement, LensSystem};
+pub use projection::{FisheyeMapping, Projection};
+pub use stereo::{StereoBuilder, StereoCamera, StereoLayout};
+
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
 use indicatif::ProgressBar;
 use rand::Rng;
 
 use crate::{
-    math::{degrees_to_radians, interval::Interval, HitResult, Hittable, Ray, Vec3},
-    renderers::{Image, Renderer},
+    math::{
+        backgrounds::{Background, VerticalGradient},
+        degrees_to_radians,
+        interval::Interval,
+        HitResult, Hittable, Ray, Vec3,
+    },
+    renderers::Image,
 };
 
-pub struct CameraBuilder {}
+/// Configures and validates the settings of a `Camera`
+#[derive(Clone)]
+pub struct CameraBuilder {
+    image_width: usize,
+    image_height: usize,
+    /// When set, the height is derived from the width instead
+    aspect_ratio: Option<f64>,
+    samples_per_pixel: u16,
+    max_depth: i32,
+    vfov: f64,
+    lookfrom: Vec3,
+    lookat: Vec3,
+    vup: Vec3,
+    defocus_angle: f64,
+    focus_dist: f64,
+    projection: Projection,
+    /// Lens system and the diagonal of the film behind it
+    lens: Option<(LensSystem, f64)>,
+    /// Moves the viewport along `u` without turning the camera, so that the eyes of a stereo
+    /// camera converge
+    viewport_shift: f64,
+    /// Signed distance of the eye of an omni-directional stereo panorama from the camera center
+    eye_offset: f64,
+    threads: usize,
+    background: Arc<dyn Background>,
+}
+
+/// Error returned when the camera settings cannot produce valid rays
+#[derive(Debug, Clone, PartialEq)]
+pub enum CameraError {
+    /// The image has no pixels
+    ZeroResolution {
+        width: usize,
+        height: usize,
+    },
+    /// The aspect ratio is not a positive number
+    InvalidAspectRatio(f64),
+    /// The vertical field of view must lie strictly between 0 and 180 degrees
+    InvalidFieldOfView(f64),
+    /// `lookfrom` and `lookat` are the same point, so there is no view direction
+    ZeroViewDirection,
+    /// The up vector is zero or parallel to the view direction, so the camera has no orientation
+    VupParallelToView,
+    /// The focus distance must be positive
+    InvalidFocusDistance(f64),
+    /// The defocus angle must lie in `[0, 180)` degrees
+    InvalidDefocusAngle(f64),
+    /// The view height of an orthographic projection must be positive
+    InvalidViewHeight(f64),
+    /// The field of view of a fisheye projection must lie in `(0, 360]` degrees
+    InvalidFisheyeFieldOfView(f64),
+    /// The distance between the eyes of a stereo camera must not be negative
+    InvalidInterocularDistance(f64),
+    /// The convergence distance of a stereo camera must be positive
+    InvalidConvergenceDistance(f64),
+    /// Stereo cameras support the perspective and the equirectangular projection
+    UnsupportedStereoProjection(Projection),
+    /// Stereo cameras cannot use a lens system
+    UnsupportedStereoLens,
+    /// The film diagonal of a lens system must be positive
+    InvalidFilmDiagonal(f64),
+    /// Lens systems only support the perspective projection
+    UnsupportedLensProjection(Projection),
+    /// The lens system cannot form a sharp image of the focus plane at this distance
+    LensCannotFocus(f64),
+    /// No light gets through the lens system to the center of the film
+    OpaqueLens,
+    ZeroSamples,
+    /// Rays must be allowed to bounce at least once
+    InvalidMaxDepth(i32),
+}
+
+impl fmt::Display for CameraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CameraError::ZeroResolution { width, height } => {
+                write!(f, "image resolution {}x{} has no pixels", width, height)
+            }
+            CameraError::InvalidAspectRatio(ratio) => write!(f, "invalid aspect ratio {}", ratio),
+            CameraError::InvalidFieldOfView(vfov) => {
+                write!(f, "vertical field of view {} is not within (0, 180)", vfov)
+            }
+            CameraError::ZeroViewDirection => write!(f, "lookfrom and lookat are the same point"),
+            CameraError::VupParallelToView => {
+                write!(f, "the up vector is parallel to the view direction")
+            }
+            CameraError::InvalidFocusDistance(dist) => {
+                write!(f, "focus distance {} is not positive", dist)
+            }
+            CameraError::InvalidDefocusAngle(angle) => {
+                write!(f, "defocus angle {} is not within [0, 180)", angle)
+            }
+            CameraError::InvalidViewHeight(height) => {
+                write!(f, "orthographic view height {} is not positive", height)
+            }
+            CameraError::InvalidFisheyeFieldOfView(fov) => {
+                write!(f, "fisheye field of view {} is not within (0, 360]", fov)
+            }
+            CameraError::InvalidInterocularDistance(distance) => {
+                write!(f, "interocular distance {} is negative", distance)
+            }
+            CameraError::InvalidConvergenceDistance(distance) => {
+                write!(f, "convergence distance {} is not positive", distance)
+            }
+            CameraError::UnsupportedStereoProjection(projection) => {
+                write!(
+                    f,
+                    "stereo cameras don't support the {:?} projection",
+                    projection
+                )
+            }
+            CameraError::UnsupportedStereoLens => {
+                write!(f, "stereo cameras don't support lens systems")
+            }
+            CameraError::InvalidFilmDiagonal(diagonal) => {
+                write!(f, "film diagonal {} is not positive", diagonal)
+            }
+            CameraError::UnsupportedLensProjection(projection) => {
+                write!(
+                    f,
+                    "lens systems don't support the {:?} projection",
+                    projection
+                )
+            }
+            CameraError::LensCannotFocus(dist) => {
+                write!(f, "the lens system cannot focus at distance {}", dist)
+            }
+            CameraError::OpaqueLens => write!(f, "no light gets through the lens system"),
+            CameraError::ZeroSamples => write!(f, "at least one sample per pixel is needed"),
+            CameraError::InvalidMaxDepth(depth) => {
+                write!(f, "max depth {} is not positive", depth)
+            }
+        }
+    }
+}
+
+impl Error for CameraError {}
+
+impl CameraBuilder {
+    pub fn new() -> Self {
+        CameraBuilder {
+            image_width: 400,
+            image_height: 225,
+            aspect_ratio: None,
+            samples_per_pixel: 20,
+            max_depth: 10,
+            vfov: 90.0,
+            lookfrom: Vec3::zero(),
+            lookat: Vec3::new(0.0, 0.0, -1.0),
+            vup: Vec3::new(0.0, 1.0, 0.0),
+            defocus_angle: 0.0,
+            focus_dist: 10.0,
+            projection: Projection::Perspective,
+            lens: None,
+            viewport_shift: 0.0,
+            eye_offset: 0.0,
+            threads: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            background: Arc::new(VerticalGradient::sky()),
+        }
+    }
+
+    /// Size of the rendered images in pixels. Defaults to 400x225.
+    pub fn resolution(mut self, image_width: usize, image_height: usize) -> Self {
+        self.image_width = image_width;
+        self.image_height = image_height;
+        self.aspect_ratio = None;
+        self
+    }
 
-const FOCAL_LENGTH: f64 = 1.0;
+    /// Renders images `image_width` pixels wide with the height following from the aspect ratio
+    /// (width / height)
+    pub fn aspect_ratio(mut self, image_width: usize, aspect_ratio: f64) -> Self {
+        self.image_width = image_width;
+        self.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    pub fn samples_per_pixel(mut self, samples: u16) -> Self {
+        self.samples_per_pixel = samples;
+        self
+    }
+
+    /// Maximum number of times a ray bounces around the scene
+    pub fn max_depth(mut self, depth: i32) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Vertical field of view in degrees
+    pub fn vfov(mut self, vfov: f64) -> Self {
+        self.vfov = vfov;
+        self
+    }
+
+    /// Where the camera is looking from
+    pub fn lookfrom(mut self, lookfrom: Vec3) -> Self {
+        self.lookfrom = lookfrom;
+        self
+    }
+
+    /// The point the camera is looking at
+    pub fn lookat(mut self, lookat: Vec3) -> Self {
+        self.lookat = lookat;
+        self
+    }
+
+    /// The up direction of the camera. It does not have to be perpendicular to the view direction.
+    pub fn vup(mut self, vup: Vec3) -> Self {
+        self.vup = vup;
+        self
+    }
+
+    /// Angle of the cone from the focus plane to the lens in degrees. `0` keeps everything sharp.
+    pub fn defocus_angle(mut self, defocus_angle: f64) -> Self {
+        self.defocus_angle = defocus_angle;
+        self
+    }
+
+    /// Distance from `lookfrom` to the plane that is in perfect focus
+    pub fn focus_dist(mut self, focus_dist: f64) -> Self {
+        self.focus_dist = focus_dist;
+        self
+    }
+
+    /// How pixels are mapped to rays. Defaults to a perspective projection.
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Traces the rays through a lens system instead of the thin lens, which adds its distortion,
+    /// vignetting and bokeh. The film diagonal, in the same units as the lens, sets the field of
+    /// view together with the focal length, so `vfov` and `defocus_angle` are ignored. The front
+    /// of the lens sits at `lookfrom` and the film is moved so that `focus_dist` is in focus.
+    pub fn lens(mut self, lens: LensSystem, film_diagonal: f64) -> Self {
+        self.lens = Some((lens, film_diagonal));
+        self
+    }
+
+    /// Number of threads used for rendering. Defaults to the available parallelism.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// What the rays that miss the scene see. Defaults to the sky gradient.
+    pub fn background(mut self, background: impl Background + 'static) -> Self {
+        self.background = Arc::new(background);
+        self
+    }
+
+    /// Checks the settings and creates the camera
+    pub fn build(self) -> Result<Camera, CameraError> {
+        let image_height = match self.aspect_ratio {
+            Some(ratio) if !(ratio.is_finite() && ratio > 0.0) => {
+                return Err(CameraError::InvalidAspectRatio(ratio))
+            }
+            Some(ratio) => ((self.image_width as f64 / ratio) as usize).max(1),
+            None => self.image_height,
+        };
+        if self.image_width == 0 || image_height == 0 {
+            return Err(CameraError::ZeroResolution {
+                width: self.image_width,
+                height: image_height,
+            });
+        }
+        // Written so that NaN fails the checks as well
+        if !(self.vfov > 0.0 && self.vfov < 180.0) {
+            return Err(CameraError::InvalidFieldOfView(self.vfov));
+        }
+        let view = self.lookat - self.lookfrom;
+        if view.length() == 0.0 || view.length().is_nan() {
+            return Err(CameraError::ZeroViewDirection);
+        }
+        // A zero up vector makes this NaN
+        let sin_angle = self.vup.cross(&view).length() / (self.vup.length() * view.length());
+        if sin_angle.is_nan() || sin_angle < 1e-8 {
+            return Err(CameraError::VupParallelToView);
+        }
+        if !(self.focus_dist > 0.0 && self.focus_dist.is_finite()) {
+            return Err(CameraError::InvalidFocusDistance(self.focus_dist));
+        }
+        if !(self.defocus_angle >= 0.0 && self.defocus_angle < 180.0) {
+            return Err(CameraError::InvalidDefocusAngle(self.defocus_angle));
+        }
+        match self.projection {
+            Projection::Orthographic { view_height }
+                if !(view_height > 0.0 && view_height.is_finite()) =>
+            {
+                return Err(CameraError::InvalidViewHeight(view_height));
+            }
+            Projection::Fisheye { fov, .. } if !(fov > 0.0 && fov <= 360.0) => {
+                return Err(CameraError::InvalidFisheyeFieldOfView(fov));
+            }
+            _ => (),
+        }
+        if let Some((_, film_diagonal)) = self.lens {
+            if !(film_diagonal > 0.0 && film_diagonal.is_finite()) {
+                return Err(CameraError::InvalidFilmDiagonal(film_diagonal));
+            }
+            if self.projection != Projection::Perspective {
+                return Err(CameraError::UnsupportedLensProjection(self.projection));
+            }
+        }
+        if self.samples_per_pixel == 0 {
+            return Err(CameraError::ZeroSamples);
+        }
+        if self.max_depth <= 0 {
+            return Err(CameraError::InvalidMaxDepth(self.max_depth));
+        }
+
+        let mut pixel_scale_factor = 1.0 / self.samples_per_pixel as f64;
+        let (mut film_width, mut film_height) = (0.0, 0.0);
+        let lens = match self.lens {
+            Some((lens, film_diagonal)) => {
+                let lens = lens
+                    .focus(self.focus_dist)
+                    .ok_or(CameraError::LensCannotFocus(self.focus_dist))?;
+                // Blocked rays darken the image, so scale it for the center to keep its brightness
+                let transmission = lens.axial_transmission();
+                if transmission == 0.0 {
+                    return Err(CameraError::OpaqueLens);
+                }
+                pixel_scale_factor /= transmission;
+
+                let diagonal = (self.image_width as f64).hypot(image_height as f64);
+                film_width = film_diagonal * self.image_width as f64 / diagonal;
+                film_height = film_diagonal * image_height as f64 / diagonal;
+                Some(lens)
+            }
+            None => None,
+        };
+
+        let mut camera = Camera {
+            samples_per_pixel: self.samples_per_pixel,
+            pixel_scale_factor,
+            camera_center: Vec3::zero(),
+            pixel_delta_u: Vec3::zero(),
+            pixel_delta_v: Vec3::zero(),
+            pixel00_loc: Vec3::zero(),
+            image_width: self.image_width,
+            image_height,
+            max_depth: self.max_depth,
+            vfov: self.vfov,
+            lookfrom: self.lookfrom,
+            lookat: self.lookat,
+            vup: self.vup,
+            u: Vec3::zero(),
+            v: Vec3::zero(),
+            w: Vec3::zero(),
+            defocus_angle: self.defocus_angle,
+            focus_dist: self.focus_dist,
+            defocus_disk_u: Vec3::zero(),
+            defocus_disk_v: Vec3::zero(),
+            projection: self.projection,
+            viewport_shift: self.viewport_shift,
+            eye_offset: self.eye_offset,
+            lens,
+            film_width,
+            film_height,
+            threads: self.threads,
+            background: self.background,
+        };
+        camera.initialize();
+        Ok(camera)
+    }
+}
+
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Width and height of the square tiles the image is split into when rendering
+const TILE_SIZE: usize = 32;
+
+/// A rectangular region of the image that is rendered by a single thread
+#[derive(Clone, Copy)]
+struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// Per-pixel geometry of the first hit of a ray through each pixel center, in row-major order.
+/// Useful as extra channels for compositing or denoising.
+pub struct AuxBuffers {
+    /// Distance of the hit along the viewing direction (from the camera center for the panoramic
+    /// projections), infinite where the ray escapes
+    pub depth: Vec<f64>,
+    /// World-space shading normal, zero where the ray escapes
+    pub normals: Vec<Vec3>,
+}
 
 pub struct Camera {
     samples_per_pixel: u16,
@@ -18,7 +435,8 @@ pub struct Camera {
     pixel_delta_u: Vec3,
     pixel_delta_v: Vec3,
     pixel00_loc: Vec3,
-    image: Image,
+    image_width: usize,
+    image_height: usize,
     max_depth: i32,
     /// Vertical FOV
     vfov: f64,
@@ -41,37 +459,23 @@ pub struct Camera {
     defocus_disk_v: Vec3,
     /// Defocus disk horizontal radius
     defocus_disk_u: Vec3,
+    projection: Projection,
+    /// Offset of the viewport along `u`
+    viewport_shift: f64,
+    /// Signed distance of the eye of an omni-directional stereo panorama from the camera center
+    eye_offset: f64,
+    /// Focused lens system that replaces the thin lens
+    lens: Option<LensSystem>,
+    /// Size of the film behind the lens system
+    film_width: f64,
+    film_height: f64,
+    /// Number of threads that render tiles in parallel
+    threads: usize,
+    /// What the rays that miss the scene see
+    background: Arc<dyn Background>,
 }
 
 impl Camera {
-    pub fn new(image: Image) -> Self {
-        let vfov = 90.0;
-        let mut camera = Self {
-            defocus_disk_v: Vec3::zero(),
-            defocus_disk_u: Vec3::zero(),
-            defocus_angle: 0.0,
-            focus_dist: 10.0,
-            samples_per_pixel: 20,
-            max_depth: 10,
-            pixel_scale_factor: 1.0 / 20.0,
-            camera_center: Vec3::new(0.0, 0.0, 0.0),
-            pixel_delta_u: Vec3::zero(),
-            pixel_delta_v: Vec3::zero(),
-            pixel00_loc: Vec3::zero(),
-            image,
-            vfov,
-            lookat: Vec3::new(0.0, 0.0, -1.0),
-            lookfrom: Vec3::zero(),
-            u: Vec3::zero(),
-            vup: Vec3::new(0.0, 1.0, 0.0),
-            v: Vec3::zero(),
-            w: Vec3::zero(),
-        };
-
-        camera.initialize();
-        camera
-    }
-
     fn sample_square() -> Vec3 {
         let mut rng = rand::rng();
         Vec3::new(
@@ -81,107 +485,260 @@ impl Camera {
         )
     }
 
-    pub fn set_aperture(&mut self, defocus_angle: f64, focus_dist: f64) {
-        self.defocus_angle = defocus_angle;
-        self.focus_dist = focus_dist;
-        self.initialize();
-    }
-
     /// Construct a ray with the origin point randomly sampled from the defocus disk and pointing
-    /// through the pixel at (i,j)
-    fn get_ray(&self, i: usize, j: usize) -> Ray {
-        let offset = Camera::sample_square();
-        let pixel_sample = self.pixel00_loc
-            + (i as f64 + offset.x) * self.pixel_delta_u
-            + (j as f64 + offset.y) * self.pixel_delta_v;
-        let ray_origin = if self.defocus_angle <= 0.0 {
-            self.camera_center
+    /// through the pixel at (i,j). Returns `None` if the projection doesn't cover the pixel.
+    fn get_ray(&self, i: usize, j: usize) -> Option<Ray> {
+        let lens_offset = if self.lens.is_some() {
+            Vec3::random_in_unit_disk()
+        } else if self.defocus_angle <= 0.0 {
+            Vec3::zero()
         } else {
             self.defocus_disk_sample()
         };
-        let ray_dir = pixel_sample - ray_origin;
-        Ray::new(ray_origin, ray_dir)
+        self.primary_ray(i, j, Camera::sample_square(), lens_offset)
+    }
+
+    /// The ray through the point `pixel_offset` away from the center of the pixel at (i,j),
+    /// starting `lens_offset` away from the center of the lens. With a lens system the offset is
+    /// a point in the unit disk on its rear surface. The panoramic projections ignore the lens.
+    fn primary_ray(
+        &self,
+        i: usize,
+        j: usize,
+        pixel_offset: Vec3,
+        lens_offset: Vec3,
+    ) -> Option<Ray> {
+        // Continuous image coordinates of the sample
+        let x = i as f64 + 0.5 + pixel_offset.x;
+        let y = j as f64 + 0.5 + pixel_offset.y;
+        let pixel_sample = self.pixel00_loc
+            + (i as f64 + pixel_offset.x) * self.pixel_delta_u
+            + (j as f64 + pixel_offset.y) * self.pixel_delta_v;
+        match self.projection {
+            Projection::Perspective => match &self.lens {
+                Some(lens) => {
+                    // The lens turns the image upside down, so the film is rotated as well
+                    let film = Vec3::new(
+                        (0.5 - x / self.image_width as f64) * self.film_width,
+                        (y / self.image_height as f64 - 0.5) * self.film_height,
+                        0.0,
+                    );
+                    let ray = lens.trace_from_film(film, lens_offset)?;
+                    // Lens space has the same axes as camera space, but the front of the lens at
+                    // the camera center
+                    let origin = ray.origin - Vec3::new(0.0, 0.0, lens.length());
+                    Some(Ray::new(
+                        self.camera_center + self.to_world(origin),
+                        self.to_world(ray.dir),
+                    ))
+                }
+                None => {
+                    let ray_origin = self.camera_center + lens_offset;
+                    Some(Ray::new(ray_origin, pixel_sample - ray_origin))
+                }
+            },
+            Projection::Orthographic { .. } => {
+                // The viewport lies in the plane of the lens, so without defocus the rays
+                // leave it parallel to the view direction and meet again on the focus plane
+                let focus_point = pixel_sample - self.focus_dist * self.w;
+                let ray_origin = pixel_sample + lens_offset;
+                Some(Ray::new(ray_origin, focus_point - ray_origin))
+            }
+            Projection::Fisheye { fov, mapping } => projection::fisheye_direction(
+                fov,
+                mapping,
+                x,
+                y,
+                self.image_width,
+                self.image_height,
+            )
+            .map(|dir| self.camera_ray(dir)),
+            Projection::Equirectangular => {
+                let mut ray = self.camera_ray(projection::equirectangular_direction(
+                    x,
+                    y,
+                    self.image_width,
+                    self.image_height,
+                ));
+                // For stereo panoramas the eyes sit on a circle, always to the side of the
+                // horizontal viewing direction
+                let side = projection::ods_side(x, self.image_width);
+                ray.origin = ray.origin + self.eye_offset * self.to_world(side);
+                Some(ray)
+            }
+        }
+    }
+
+    /// The ray from the camera center in the direction given in camera space (`x` right, `y` up,
+    /// `z` forward)
+    fn camera_ray(&self, dir: Vec3) -> Ray {
+        Ray::new(self.camera_center, self.to_world(dir))
+    }
+
+    /// Converts a direction from camera space (`x` right, `y` up, `z` forward) to world space
+    fn to_world(&self, dir: Vec3) -> Vec3 {
+        dir.x * self.u + dir.y * self.v - dir.z * self.w
     }
 
+    /// Random offset from the center of the defocus disk
     fn defocus_disk_sample(&self) -> Vec3 {
         let rand_disk = Vec3::random_in_unit_disk();
-        self.camera_center + rand_disk.x * self.defocus_disk_u + rand_disk.y * self.defocus_disk_v
+        rand_disk.x * self.defocus_disk_u + rand_disk.y * self.defocus_disk_v
     }
 
-    pub fn render(&mut self, renderer: impl Renderer, world: &impl Hittable) {
-        let bar = ProgressBar::new(self.image.height as u64);
-
-        for j in 0..self.image.height {
-            bar.inc(1);
-            for i in 0..self.image.width {
-                let mut color = Vec3::new(0.0, 0.0, 0.0);
+    /// Renders the world into a new frame buffer
+    pub fn render(&self, world: &impl Hittable) -> Image {
+        let tiles = self.tiles();
+        let bar = ProgressBar::new(tiles.len() as u64);
+
+        // Every worker takes the next unrendered tile until there are none left, so threads that
+        // get cheap tiles (e.g. only sky) don't sit idle.
+        let next_tile = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        let rendered: Vec<(Tile, Vec<Vec3>)> = thread::scope(|s| {
+            for _ in 0..self.threads {
+                let sender = sender.clone();
+                let (tiles, next_tile) = (&tiles, &next_tile);
+                s.spawn(move || {
+                    while let Some(tile) = tiles.get(next_tile.fetch_add(1, Ordering::Relaxed)) {
+                        if sender.send((*tile, self.render_tile(tile, world))).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            // Only the workers hold senders now, so the receiver stops once they are all done
+            drop(sender);
 
-                for _ in 0..self.samples_per_pixel {
-                    let ray = self.get_ray(i, j);
-                    color = color + Camera::ray_color(&ray, self.max_depth, world);
-                }
+            receiver.iter().inspect(|_| bar.inc(1)).collect()
+        });
 
-                color = color * self.pixel_scale_factor;
+        bar.finish();
 
-                self.image.set_pixel(i, j, color.into());
+        let mut image = Image::new(self.image_width, self.image_height);
+        for (tile, pixels) in rendered {
+            for (k, color) in pixels.into_iter().enumerate() {
+                image.set_pixel(tile.x + k % tile.width, tile.y + k / tile.width, color);
             }
         }
-
-        bar.finish();
-        renderer.draw(&self.image);
+        image
     }
 
-    pub fn set_samples_per_pixel(&mut self, samples: u16) {
-        self.samples_per_pixel = samples;
-        self.pixel_scale_factor = 1.0 / samples as f64;
-    }
+    /// Traces a single ray through the center of every pixel and records what it hits first
+    pub fn render_aux_buffers(&self, world: &impl Hittable) -> AuxBuffers {
+        let pixel_count = self.image_width * self.image_height;
+        let mut buffers = AuxBuffers {
+            depth: Vec::with_capacity(pixel_count),
+            normals: Vec::with_capacity(pixel_count),
+        };
 
-    pub fn set_max_depth(&mut self, depth: i32) {
-        self.max_depth = depth;
+        for j in 0..self.image_height {
+            for i in 0..self.image_width {
+                let hit = self
+                    .primary_ray(i, j, Vec3::zero(), Vec3::zero())
+                    .map(|ray| world.hit(&ray, Interval::new(0.0001, f64::INFINITY)));
+                match hit {
+                    Some(HitResult::Hit(rec)) => {
+                        let offset = rec.point - self.camera_center;
+                        let depth = match self.projection {
+                            Projection::Perspective | Projection::Orthographic { .. } => {
+                                -offset.dot(&self.w)
+                            }
+                            // Panoramas have no single viewing direction
+                            Projection::Fisheye { .. } | Projection::Equirectangular => {
+                                offset.length()
+                            }
+                        };
+                        buffers.depth.push(depth);
+                        buffers.normals.push(rec.normal);
+                    }
+                    _ => {
+                        buffers.depth.push(f64::INFINITY);
+                        buffers.normals.push(Vec3::zero());
+                    }
+                }
+            }
+        }
+        buffers
     }
 
-    pub fn set_vfov(&mut self, vfov: f64) {
-        self.vfov = vfov;
-        self.initialize();
+    /// Splits the image into tiles of at most `TILE_SIZE` x `TILE_SIZE` pixels
+    fn tiles(&self) -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        for y in (0..self.image_height).step_by(TILE_SIZE) {
+            for x in (0..self.image_width).step_by(TILE_SIZE) {
+                tiles.push(Tile {
+                    x,
+                    y,
+                    width: TILE_SIZE.min(self.image_width - x),
+                    height: TILE_SIZE.min(self.image_height - y),
+                });
+            }
+        }
+        tiles
     }
 
-    pub fn set_camera_pos(&mut self, lookfrom: Vec3, lookat: Vec3) {
-        self.lookat = lookat;
-        self.lookfrom = lookfrom;
-        self.initialize();
+    /// Renders the pixels of the tile in row-major order
+    fn render_tile(&self, tile: &Tile, world: &impl Hittable) -> Vec<Vec3> {
+        let mut pixels = Vec::with_capacity(tile.width * tile.height);
+
+        for j in tile.y..tile.y + tile.height {
+            for i in tile.x..tile.x + tile.width {
+                let mut color = Vec3::new(0.0, 0.0, 0.0);
+
+                for _ in 0..self.samples_per_pixel {
+                    if let Some(ray) = self.get_ray(i, j) {
+                        color = color + self.ray_color(&ray, self.max_depth, world);
+                    }
+                }
+
+                pixels.push(color * self.pixel_scale_factor);
+            }
+        }
+
+        pixels
     }
 
     fn initialize(&mut self) {
-        let image_width = self.image.width;
-        let image_height = self.image.height;
+        let image_width = self.image_width;
+        let image_height = self.image_height;
 
         self.camera_center = self.lookfrom;
 
-        // Calculate viewport dimensions
-        let theta = degrees_to_radians(self.vfov);
-        let h = (theta / 2.0).tan();
-        let viewport_height = 2.0 * h * self.focus_dist;
+        // Calculate viewport dimensions. The perspective viewport lies on the focus plane, the
+        // orthographic one goes through the camera center. The panoramic projections don't use
+        // the viewport, only the basis vectors.
+        let (viewport_height, viewport_dist) = match self.projection {
+            Projection::Perspective | Projection::Fisheye { .. } | Projection::Equirectangular => {
+                let theta = degrees_to_radians(self.vfov);
+                let h = (theta / 2.0).tan();
+                (2.0 * h * self.focus_dist, self.focus_dist)
+            }
+            Projection::Orthographic { view_height } => (view_height, 0.0),
+        };
 
         // We re-calculate the aspect ratio because when calculating the image width we can
         // introduce rounding errors.
         let viewport_width = viewport_height * (image_width as f64 / image_height as f64);
 
         self.w = (self.lookfrom - self.lookat).unit();
-        self.u = self.vup.cross(&self.w);
+        // `vup` is not necessarily perpendicular to `w`, so the cross product has to be normalized
+        self.u = self.vup.cross(&self.w).unit();
         self.v = self.w.cross(&self.u);
 
         // We are using right-handed coordinates: y is up, x is right, negative z is the camera dir
         // Vectors describing the viewport
         let viewport_u = viewport_width * self.u;
-        let viewport_v = viewport_height * -1.0 * self.v;
+        let viewport_v = -viewport_height * self.v;
 
         // Pixel-to-pixel deltas
         self.pixel_delta_u = viewport_u / image_width as f64;
         self.pixel_delta_v = viewport_v / image_height as f64;
 
         // Location of the upper left pixel
-        let viewport_upper_left = self.camera_center
-            - (self.focus_dist * self.w)
+        let viewport_upper_left = self.camera_center - (viewport_dist * self.w)
+            + (self.viewport_shift * self.u)
             - (viewport_u / 2.0)
             - (viewport_v / 2.0);
         self.pixel00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
@@ -192,7 +749,7 @@ impl Camera {
         self.defocus_disk_v = self.v * defocus_radius;
     }
 
-    fn ray_color(r: &Ray, depth: i32, world: &impl Hittable) -> Vec3 {
+    fn ray_color(&self, r: &Ray, depth: i32, world: &impl Hittable) -> Vec3 {
         if depth <= 0 {
             return Vec3::zero();
         }
@@ -200,18 +757,17 @@ impl Camera {
         // cause rays to bounce inside the object
         // The phenomen is called "shadow acne"
         if let HitResult::Hit(rec) = world.hit(r, Interval::new(0.0001, f64::INFINITY)) {
+            let emitted = rec.mat.emitted(r, &rec);
             // We choose the direction according to the Lambertian distribution.
             if let Some(scatter_res) = rec.mat.scatter(r, &rec) {
-                return scatter_res.attenuation
-                    * Camera::ray_color(&scatter_res.scattered, depth - 1, world);
+                return emitted
+                    + scatter_res.attenuation
+                        * self.ray_color(&scatter_res.scattered, depth - 1, world);
             } else {
-                return Vec3::zero();
+                return emitted;
             }
         }
 
-        let unit_dir = r.dir.unit();
-        let a = 0.5 * (unit_dir.y + 1.0);
-
-        (1.0 - a) * Vec3::new(1.0, 1.0, 1.0) + a * Vec3::new(0.5, 0.7, 1.0)
+        self.background.color(r)
     }
 }
@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use crate::{
+    loaders::{self, LoadError},
+    math::{refract, Ray, Vec3},
+};
+
+/// One spherical surface of a lens system. Lengths use the units of the prescription.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LensElement {
+    /// Radius of curvature, positive when the center of the sphere lies on the film side. `0`
+    /// makes the surface flat, like the aperture stop.
+    pub radius: f64,
+    /// Distance along the optical axis to the next surface, or to the film for the last one
+    pub thickness: f64,
+    /// Index of refraction between this surface and the next one. `0` stands for air.
+    pub ior: f64,
+    /// Diameter of the opening that lets light through
+    pub aperture: f64,
+}
+
+/// A sequence of lens elements and an aperture stop in front of the film, ordered from the
+/// front of the lens to the film.
+///
+/// Lens space has the film in the plane `z = 0` and the optical axis along `z`, pointing towards
+/// the scene.
+#[derive(Clone, Debug)]
+pub struct LensSystem {
+    elements: Vec<LensElement>,
+}
+
+impl LensSystem {
+    /// Returns `None` if there are no elements
+    pub fn new(elements: Vec<LensElement>) -> Option<Self> {
+        (!elements.is_empty()).then_some(LensSystem { elements })
+    }
+
+    /// Loads a lens prescription, see `loaders::parse_lens` for the format
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        loaders::load_lens(path)
+    }
+
+    pub fn elements(&self) -> &[LensElement] {
+        &self.elements
+    }
+
+    /// Converts the lengths to different units, e.g. `0.001` turns millimeters into meters
+    pub fn scaled(&self, factor: f64) -> Self {
+        let elements = self
+            .elements
+            .iter()
+            .map(|e| LensElement {
+                radius: e.radius * factor,
+                thickness: e.thickness * factor,
+                aperture: e.aperture * factor,
+                ..*e
+            })
+            .collect();
+        LensSystem { elements }
+    }
+
+    /// Effective focal length of the lens, or `None` if it doesn't converge light
+    pub fn focal_length(&self) -> Option<f64> {
+        // A paraxial ray parallel to the axis crosses the axis in the focal point. The focal
+        // length is measured from where the incoming and the outgoing ray meet.
+        let height = self.paraxial_height();
+        let ray = Ray::new(
+            Vec3::new(height, 0.0, self.length() + 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        let ray = self.trace_from_scene(&ray)?;
+        let focal_point = ray.at(-ray.origin.x / ray.dir.x).z;
+        let principal_plane = ray.at((height - ray.origin.x) / ray.dir.x).z;
+        let focal_length = principal_plane - focal_point;
+        (focal_length > 0.0 && focal_length.is_finite()).then_some(focal_length)
+    }
+
+    /// Distance from the film to the front surface
+    pub(super) fn length(&self) -> f64 {
+        self.elements.iter().map(|e| e.thickness).sum()
+    }
+
+    fn rear(&self) -> &LensElement {
+        &self.elements[self.elements.len() - 1]
+    }
+
+    /// Distance from the axis at which rays still behave paraxially
+    fn paraxial_height(&self) -> f64 {
+        0.001 * self.elements[0].aperture
+    }
+
+    /// Moves the film so that points `distance` in front of the front surface are in focus.
+    /// Returns `None` if the lens cannot form a real image of them.
+    pub fn focus(&self, distance: f64) -> Option<Self> {
+        // Light from a point on the axis converges in its image behind the lens
+        let length = self.length();
+        let source = Vec3::new(0.0, 0.0, length + distance);
+        let target = Vec3::new(self.paraxial_height(), 0.0, length);
+        let ray = self.trace_from_scene(&Ray::new(source, target - source))?;
+        let t = -ray.origin.x / ray.dir.x;
+        if !(t > 0.0 && t.is_finite()) {
+            return None;
+        }
+
+        let film_distance = self.rear().thickness - ray.at(t).z;
+        if film_distance <= 0.0 {
+            return None;
+        }
+        let mut focused = self.clone();
+        let last = focused.elements.len() - 1;
+        focused.elements[last].thickness = film_distance;
+        Some(focused)
+    }
+
+    /// The share of the light from a point on the rear surface that reaches the center of the
+    /// film. Rays aimed at the rear surface outside of the exit pupil get blocked.
+    pub(super) fn axial_transmission(&self) -> f64 {
+        const STEPS: usize = 64;
+        let mut total = 0;
+        let mut passed = 0;
+        for j in 0..STEPS {
+            for i in 0..STEPS {
+                let x = 2.0 * (i as f64 + 0.5) / STEPS as f64 - 1.0;
+                let y = 2.0 * (j as f64 + 0.5) / STEPS as f64 - 1.0;
+                if x * x + y * y > 1.0 {
+                    continue;
+                }
+                total += 1;
+                if self
+                    .trace_from_film(Vec3::zero(), Vec3::new(x, y, 0.0))
+                    .is_some()
+                {
+                    passed += 1;
+                }
+            }
+        }
+        passed as f64 / total as f64
+    }
+
+    /// The ray from the point `film` towards the point `rear_sample` on the rear surface, given
+    /// in the unit disk, after it leaves the front of the lens. Returns `None` if the ray is
+    /// blocked by an aperture or totally reflected.
+    pub(super) fn trace_from_film(&self, film: Vec3, rear_sample: Vec3) -> Option<Ray> {
+        let rear = self.rear();
+        let target = Vec3::new(
+            rear_sample.x * rear.aperture / 2.0,
+            rear_sample.y * rear.aperture / 2.0,
+            rear.thickness,
+        );
+        let mut ray = Ray::new(film, target - film);
+        let mut z = 0.0;
+        for (idx, element) in self.elements.iter().enumerate().rev() {
+            z += element.thickness;
+            ray = self.surface(idx, z, &ray, self.ior_behind(idx), self.ior_in_front(idx))?;
+        }
+        Some(ray)
+    }
+
+    /// Traces a ray coming from the scene through the lens towards the film
+    fn trace_from_scene(&self, ray: &Ray) -> Option<Ray> {
+        let mut ray = Ray::new(ray.origin, ray.dir);
+        let mut z = self.length();
+        for (idx, element) in self.elements.iter().enumerate() {
+            ray = self.surface(idx, z, &ray, self.ior_in_front(idx), self.ior_behind(idx))?;
+            z -= element.thickness;
+        }
+        Some(ray)
+    }
+
+    /// Index of refraction between the surface and the film
+    fn ior_behind(&self, idx: usize) -> f64 {
+        let ior = self.elements[idx].ior;
+        if ior == 0.0 {
+            1.0
+        } else {
+            ior
+        }
+    }
+
+    /// Index of refraction between the surface and the scene
+    fn ior_in_front(&self, idx: usize) -> f64 {
+        if idx == 0 {
+            1.0
+        } else {
+            self.ior_behind(idx - 1)
+        }
+    }
+
+    /// Refracts the ray on the surface `idx` whose vertex lies at `z`
+    fn surface(&self, idx: usize, z: f64, ray: &Ray, ior_from: f64, ior_to: f64) -> Option<Ray> {
+        let element = &self.elements[idx];
+        let (t, normal) = if element.radius == 0.0 {
+            ((z - ray.origin.z) / ray.dir.z, Vec3::new(0.0, 0.0, 1.0))
+        } else {
+            let center = Vec3::new(0.0, 0.0, z - element.radius);
+            let oc = ray.origin - center;
+            let a = ray.dir.length_squared();
+            let half_b = oc.dot(&ray.dir);
+            let c = oc.length_squared() - element.radius * element.radius;
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0.0 {
+                return None;
+            }
+            // The surface is only the cap of the sphere around the vertex
+            let sqrtd = discriminant.sqrt();
+            let t = [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a]
+                .into_iter()
+                .min_by(|t1, t2| {
+                    let d1 = (ray.at(*t1).z - z).abs();
+                    let d2 = (ray.at(*t2).z - z).abs();
+                    d1.total_cmp(&d2)
+                })?;
+            (t, (ray.at(t) - center) / element.radius.abs())
+        };
+        // Also rejects NaN from rays parallel to a flat surface
+        if t <= 0.0 || t.is_nan() {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let aperture_radius = element.aperture / 2.0;
+        if point.x * point.x + point.y * point.y > aperture_radius * aperture_radius {
+            return None;
+        }
+
+        let dir = ray.dir.unit();
+        let normal = if normal.dot(&dir) > 0.0 {
+            -normal
+        } else {
+            normal
+        };
+        let ratio = ior_from / ior_to;
+        let cos_theta = (-dir.dot(&normal)).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        if ratio * sin_theta > 1.0 {
+            // Total internal reflection sends the light back into the lens barrel
+            return None;
+        }
+        Some(Ray::new(point, refract(&dir, &normal, ratio)))
+    }
+}
@@ -0,0 +1,83 @@
+use std::f64::consts::PI;
+
+use crate::math::{degrees_to_radians, Vec3};
+
+/// How the camera maps the pixel grid to rays
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+    /// Rays fan out from `lookfrom` through the viewport, covering the vertical field of view
+    #[default]
+    Perspective,
+    /// Parallel rays along the view direction, so objects keep their size regardless of the
+    /// distance. `view_height` is the height of the visible area in world units.
+    Orthographic { view_height: f64 },
+    /// A circular fisheye image inscribed in the frame, covering `fov` degrees across its
+    /// diameter (up to 360). Pixels outside of the circle stay black.
+    Fisheye { fov: f64, mapping: FisheyeMapping },
+    /// A full 360° x 180° panorama around `lookfrom` with the view direction in the center of
+    /// the image. It uses the same layout as `EnvironmentMap`, so the result can be loaded as one
+    /// if it has a 2:1 resolution and the camera looks down the negative `z` axis with the
+    /// default `vup`.
+    Equirectangular,
+}
+
+/// How the angle from the view direction grows with the distance from the center of a fisheye
+/// image
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    /// The distance is proportional to the angle
+    #[default]
+    Equidistant,
+    /// Equal areas of the image cover equal solid angles
+    Equisolid,
+}
+
+/// Direction in camera space (`x` right, `y` up, `z` forward) of the fisheye ray through the
+/// point `(x, y)` in pixels, or `None` outside of the image circle
+pub(super) fn fisheye_direction(
+    fov: f64,
+    mapping: FisheyeMapping,
+    x: f64,
+    y: f64,
+    width: usize,
+    height: usize,
+) -> Option<Vec3> {
+    let radius = width.min(height) as f64 / 2.0;
+    let dx = (x - width as f64 / 2.0) / radius;
+    let dy = (height as f64 / 2.0 - y) / radius;
+    let r = (dx * dx + dy * dy).sqrt();
+    if r > 1.0 {
+        return None;
+    }
+
+    let half_fov = degrees_to_radians(fov) / 2.0;
+    let theta = match mapping {
+        FisheyeMapping::Equidistant => r * half_fov,
+        FisheyeMapping::Equisolid => 2.0 * (r * (half_fov / 2.0).sin()).asin(),
+    };
+    let phi = dy.atan2(dx);
+    Some(Vec3::new(
+        theta.sin() * phi.cos(),
+        theta.sin() * phi.sin(),
+        theta.cos(),
+    ))
+}
+
+/// Direction in camera space (`x` right, `y` up, `z` forward) of the panorama ray through the
+/// point `(x, y)` in pixels
+pub(super) fn equirectangular_direction(x: f64, y: f64, width: usize, height: usize) -> Vec3 {
+    let longitude = (x / width as f64 - 0.5) * 2.0 * PI;
+    let latitude = (0.5 - y / height as f64) * PI;
+    Vec3::new(
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+        latitude.cos() * longitude.cos(),
+    )
+}
+
+/// Direction in camera space pointing to the right of the horizontal viewing direction of the
+/// panorama column at `x` pixels, along which the eyes of a stereo panorama are offset
+pub(super) fn ods_side(x: f64, width: usize) -> Vec3 {
+    let longitude = (x / width as f64 - 0.5) * 2.0 * PI;
+    Vec3::new(longitude.cos(), 0.0, -longitude.sin())
+}
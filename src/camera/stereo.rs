@@ -0,0 +1,161 @@
+use super::{Camera, CameraBuilder, CameraError, Projection};
+use crate::{
+    math::{Hittable, Vec3},
+    renderers::Image,
+};
+
+/// How the images of the two eyes are combined into one frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StereoLayout {
+    /// The left eye on the left half and the right eye on the right half
+    #[default]
+    SideBySide,
+    /// The left eye on the top half and the right eye on the bottom half
+    OverUnder,
+    /// The red channel of the left eye with the green and blue channels of the right eye, for
+    /// red/cyan glasses
+    Anaglyph,
+}
+
+/// Configures a `StereoCamera` on top of the settings of a `CameraBuilder`, which describe the
+/// point between the eyes
+#[derive(Clone)]
+pub struct StereoBuilder {
+    camera: CameraBuilder,
+    interocular_distance: f64,
+    /// Defaults to the focus distance when not set
+    convergence_distance: Option<f64>,
+    layout: StereoLayout,
+}
+
+impl StereoBuilder {
+    pub fn new(camera: CameraBuilder) -> Self {
+        StereoBuilder {
+            camera,
+            interocular_distance: 0.064,
+            convergence_distance: None,
+            layout: StereoLayout::default(),
+        }
+    }
+
+    /// Distance between the eyes in scene units. Defaults to 0.064, the average human eye
+    /// distance when a unit is a meter.
+    pub fn interocular_distance(mut self, distance: f64) -> Self {
+        self.interocular_distance = distance;
+        self
+    }
+
+    /// Distance of the plane that appears at the depth of the screen. Everything closer pops out
+    /// of it. Defaults to the focus distance of the camera.
+    pub fn convergence_distance(mut self, distance: f64) -> Self {
+        self.convergence_distance = Some(distance);
+        self
+    }
+
+    pub fn layout(mut self, layout: StereoLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Checks the settings and creates the cameras of both eyes. Perspective cameras get parallel
+    /// eyes with shifted viewports, so there is no vertical parallax. Equirectangular cameras
+    /// produce an omni-directional stereo (ODS) panorama, where the eyes circle around the camera
+    /// center and the convergence distance is not used.
+    pub fn build(self) -> Result<StereoCamera, CameraError> {
+        let distance = self.interocular_distance;
+        if !(distance >= 0.0 && distance.is_finite()) {
+            return Err(CameraError::InvalidInterocularDistance(distance));
+        }
+        let convergence = self.convergence_distance.unwrap_or(self.camera.focus_dist);
+        // An infinite distance keeps the eyes parallel
+        if convergence <= 0.0 || convergence.is_nan() {
+            return Err(CameraError::InvalidConvergenceDistance(convergence));
+        }
+
+        let center = self.camera.clone().build()?;
+        if center.lens.is_some() {
+            return Err(CameraError::UnsupportedStereoLens);
+        }
+        let eye = |side: f64| -> Result<Camera, CameraError> {
+            // Negative for the left eye and positive for the right one
+            let offset = side * distance / 2.0;
+            match center.projection {
+                Projection::Perspective => {
+                    let shift = offset * center.u;
+                    let mut builder = self
+                        .camera
+                        .clone()
+                        .lookfrom(center.lookfrom + shift)
+                        .lookat(center.lookat + shift);
+                    // Move the viewport back towards the center, so that the eyes agree on
+                    // points on the convergence plane
+                    builder.viewport_shift = -offset * center.focus_dist / convergence;
+                    builder.build()
+                }
+                Projection::Equirectangular => {
+                    let mut builder = self.camera.clone();
+                    builder.eye_offset = offset;
+                    builder.build()
+                }
+                projection => Err(CameraError::UnsupportedStereoProjection(projection)),
+            }
+        };
+
+        Ok(StereoCamera {
+            left: eye(-1.0)?,
+            right: eye(1.0)?,
+            layout: self.layout,
+        })
+    }
+}
+
+/// A pair of cameras rendering the scene from the viewpoints of the left and the right eye
+pub struct StereoCamera {
+    left: Camera,
+    right: Camera,
+    layout: StereoLayout,
+}
+
+impl StereoCamera {
+    /// The cameras of the left and the right eye, e.g. for rendering their auxiliary buffers
+    pub fn eyes(&self) -> (&Camera, &Camera) {
+        (&self.left, &self.right)
+    }
+
+    /// Renders both eyes at the resolution of the camera
+    pub fn render_eyes(&self, world: &impl Hittable) -> (Image, Image) {
+        (self.left.render(world), self.right.render(world))
+    }
+
+    /// Renders both eyes and combines them according to the layout. Side-by-side frames are
+    /// twice as wide and over-under frames twice as tall as the resolution of the camera.
+    pub fn render(&self, world: &impl Hittable) -> Image {
+        let (left, right) = self.render_eyes(world);
+        let (width, height) = (left.width, left.height);
+
+        match self.layout {
+            StereoLayout::SideBySide => {
+                let mut frame = Image::new(2 * width, height);
+                for y in 0..height {
+                    for x in 0..width {
+                        frame.set_pixel(x, y, *left.get_pixel(x, y));
+                        frame.set_pixel(width + x, y, *right.get_pixel(x, y));
+                    }
+                }
+                frame
+            }
+            StereoLayout::OverUnder => {
+                let pixels = left.pixels().iter().chain(right.pixels()).copied();
+                Image::from_pixels(width, 2 * height, pixels.collect())
+            }
+            StereoLayout::Anaglyph => {
+                let pixels = left
+                    .pixels()
+                    .iter()
+                    .zip(right.pixels())
+                    .map(|(l, r)| Vec3::new(l.x, r.y, r.z));
+                Image::from_pixels(width, height, pixels.collect())
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod camera;
+pub mod loaders;
 pub mod math;
 pub mod renderers;
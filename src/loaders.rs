@@ -0,0 +1,98 @@
+pub mod hdr;
+pub mod lens;
+pub mod obj;
+pub mod pfm;
+pub mod png;
+pub mod ppm;
+
+use std::{fmt, io, path::Path};
+
+pub use hdr::{load_hdr, parse_hdr};
+pub use lens::{load_lens, parse_lens};
+pub use obj::{load_mtl, load_obj, parse_mtl, parse_obj};
+pub use pfm::{load_pfm, parse_pfm};
+pub use png::{load_png, parse_png};
+pub use ppm::{load_ppm, parse_ppm};
+
+use crate::renderers::Image;
+
+/// Loads a PNG, PPM, Radiance HDR or PFM image into linear space, choosing the format based on
+/// the extension
+pub fn load_image(path: impl AsRef<Path>) -> Result<Image, LoadError> {
+    let path = path.as_ref();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("png") => load_png(path),
+        Some(ext) if ext.eq_ignore_ascii_case("ppm") => load_ppm(path),
+        Some(ext) if ext.eq_ignore_ascii_case("hdr") => load_hdr(path),
+        Some(ext) if ext.eq_ignore_ascii_case("pfm") => load_pfm(path),
+        _ => Err(LoadError::Format(format!(
+            "unsupported image format `{}`",
+            path.display()
+        ))),
+    }
+}
+
+/// Largest number of pixels the image loaders accept, so that a corrupt header cannot make them
+/// allocate unbounded memory before any pixel is read
+const MAX_PIXELS: usize = 1 << 28;
+
+/// Checks the size from an image header and returns the number of pixels
+fn pixel_count(width: usize, height: usize) -> Result<usize, LoadError> {
+    if width == 0 || height == 0 {
+        return Err(LoadError::Format("zero image size".to_string()));
+    }
+    match width.checked_mul(height) {
+        Some(count) if count <= MAX_PIXELS => Ok(count),
+        _ => Err(LoadError::Format(format!(
+            "image size {}x{} is too large",
+            width, height
+        ))),
+    }
+}
+
+/// Error returned when a file cannot be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// The file could be read, but its contents are malformed. `line` is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// The binary contents of the file are malformed or use an unsupported feature
+    Format(String),
+}
+
+impl LoadError {
+    fn parse(line: usize, message: impl Into<String>) -> Self {
+        LoadError::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "I/O error: {}", e),
+            LoadError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            LoadError::Format(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Parse { .. } | LoadError::Format(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
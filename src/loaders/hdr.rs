@@ -0,0 +1,169 @@
+use std::{fs, path::Path};
+
+use super::{pixel_count, LoadError};
+use crate::{math::Vec3, renderers::Image};
+
+/// Loads a Radiance `.hdr` (RGBE) image
+pub fn load_hdr(path: impl AsRef<Path>) -> Result<Image, LoadError> {
+    parse_hdr(&fs::read(path)?)
+}
+
+/// Parses a Radiance `.hdr` image. Supports flat, old-style and new-style run-length encoded
+/// scanlines in the standard `-Y height +X width` orientation.
+pub fn parse_hdr(data: &[u8]) -> Result<Image, LoadError> {
+    let mut pos = 0;
+    let mut line = 0;
+
+    // The header is a list of text lines terminated by an empty line
+    loop {
+        line += 1;
+        let text = read_line(data, &mut pos)
+            .ok_or_else(|| LoadError::parse(line, "unexpected end of header"))?;
+        if line == 1 {
+            if !text.starts_with("#?") {
+                return Err(LoadError::parse(line, "missing `#?RADIANCE` signature"));
+            }
+            continue;
+        }
+        if text.is_empty() {
+            break;
+        }
+        if let Some(format) = text.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(LoadError::parse(
+                    line,
+                    format!("unsupported pixel format `{}`", format),
+                ));
+            }
+        }
+    }
+
+    line += 1;
+    let resolution = read_line(data, &mut pos)
+        .ok_or_else(|| LoadError::parse(line, "missing image resolution"))?;
+    let (width, height) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => match (width.parse(), height.parse()) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(LoadError::parse(
+                    line,
+                    format!("invalid resolution `{}`", resolution),
+                ))
+            }
+        },
+        _ => {
+            return Err(LoadError::parse(
+                line,
+                format!("unsupported image orientation `{}`", resolution),
+            ))
+        }
+    };
+
+    let mut pixels = Vec::with_capacity(pixel_count(width, height)?);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        read_scanline(data, &mut pos, &mut scanline)?;
+        pixels.extend(scanline.iter().map(|&rgbe| rgbe_to_vec3(rgbe)));
+    }
+
+    Ok(Image::from_pixels(width, height, pixels))
+}
+
+/// Reads an ASCII line and moves `pos` past the newline
+fn read_line<'a>(data: &'a [u8], pos: &mut usize) -> Option<&'a str> {
+    let rest = data.get(*pos..)?;
+    let len = rest.iter().position(|&b| b == b'\n')?;
+    *pos += len + 1;
+    std::str::from_utf8(&rest[..len]).ok().map(|s| s.trim_end())
+}
+
+fn read_byte(data: &[u8], pos: &mut usize) -> Result<u8, LoadError> {
+    let byte = *data
+        .get(*pos)
+        .ok_or_else(|| LoadError::Format("unexpected end of pixel data".to_string()))?;
+    *pos += 1;
+    Ok(byte)
+}
+
+fn read_scanline(data: &[u8], pos: &mut usize, scanline: &mut [[u8; 4]]) -> Result<(), LoadError> {
+    let width = scanline.len();
+    let is_new_rle = (8..0x8000).contains(&width)
+        && data.get(*pos..*pos + 4) == Some(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8][..]);
+
+    if !is_new_rle {
+        return read_old_scanline(data, pos, scanline);
+    }
+    *pos += 4;
+
+    // New-style RLE stores each of the four components separately as runs and literal spans
+    for component in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = read_byte(data, pos)? as usize;
+            let (len, run) = if count > 128 {
+                (count - 128, true)
+            } else {
+                (count, false)
+            };
+            if len == 0 || x + len > width {
+                return Err(LoadError::Format("invalid scanline run length".to_string()));
+            }
+
+            if run {
+                let value = read_byte(data, pos)?;
+                scanline[x..x + len]
+                    .iter_mut()
+                    .for_each(|p| p[component] = value);
+            } else {
+                for p in &mut scanline[x..x + len] {
+                    p[component] = read_byte(data, pos)?;
+                }
+            }
+            x += len;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a flat scanline, where pixels `(1, 1, 1, n)` repeat the previous pixel `n` times
+fn read_old_scanline(
+    data: &[u8],
+    pos: &mut usize,
+    scanline: &mut [[u8; 4]],
+) -> Result<(), LoadError> {
+    let mut x = 0;
+    let mut shift = 0;
+    while x < scanline.len() {
+        let pixel = [
+            read_byte(data, pos)?,
+            read_byte(data, pos)?,
+            read_byte(data, pos)?,
+            read_byte(data, pos)?,
+        ];
+        if pixel[..3] == [1, 1, 1] && x > 0 {
+            // Consecutive repeat markers encode progressively more significant bytes of the count
+            let count = (pixel[3] as usize) << shift;
+            if x + count > scanline.len() {
+                return Err(LoadError::Format("invalid scanline run length".to_string()));
+            }
+            let previous = scanline[x - 1];
+            scanline[x..x + count].fill(previous);
+            x += count;
+            shift += 8;
+        } else {
+            scanline[x] = pixel;
+            x += 1;
+            shift = 0;
+        }
+    }
+    Ok(())
+}
+
+/// The three mantissas share the exponent `e`, biased by 128
+fn rgbe_to_vec3([r, g, b, e]: [u8; 4]) -> Vec3 {
+    if e == 0 {
+        return Vec3::zero();
+    }
+    let f = 2f64.powi(e as i32 - (128 + 8));
+    Vec3::new(r as f64 * f, g as f64 * f, b as f64 * f)
+}
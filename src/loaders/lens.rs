@@ -0,0 +1,78 @@
+use std::{fs, path::Path};
+
+use super::LoadError;
+use crate::camera::lens::{LensElement, LensSystem};
+
+/// Loads a lens prescription
+pub fn load_lens(path: impl AsRef<Path>) -> Result<LensSystem, LoadError> {
+    parse_lens(&fs::read_to_string(path)?)
+}
+
+/// Parses a lens prescription. Every line describes one surface, from the front of the lens to
+/// the film, with four whitespace separated numbers: the radius of curvature, the thickness (the
+/// distance to the next surface), the index of refraction behind the surface and the aperture
+/// diameter. `#` starts a comment.
+pub fn parse_lens(source: &str) -> Result<LensSystem, LoadError> {
+    let mut elements = Vec::new();
+
+    for (idx, raw_line) in source.lines().enumerate() {
+        let line = idx + 1;
+        let content = match raw_line.find('#') {
+            Some(idx) => &raw_line[..idx],
+            None => raw_line,
+        };
+        let tokens: Vec<&str> = content.split_whitespace().collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        if tokens.len() != 4 {
+            return Err(LoadError::parse(
+                line,
+                format!("expected 4 values, found {}", tokens.len()),
+            ));
+        }
+
+        let value = |idx: usize, what: &str| -> Result<f64, LoadError> {
+            tokens[idx]
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| {
+                    LoadError::parse(line, format!("invalid {} `{}`", what, tokens[idx]))
+                })
+        };
+        let element = LensElement {
+            radius: value(0, "radius")?,
+            thickness: value(1, "thickness")?,
+            ior: value(2, "index of refraction")?,
+            aperture: value(3, "aperture")?,
+        };
+
+        if element.thickness < 0.0 {
+            return Err(LoadError::parse(line, "the thickness is negative"));
+        }
+        // 0 stands for air
+        if element.ior < 0.0 || (element.ior > 0.0 && element.ior < 1.0) {
+            return Err(LoadError::parse(
+                line,
+                format!("invalid index of refraction {}", element.ior),
+            ));
+        }
+        if element.aperture <= 0.0 {
+            return Err(LoadError::parse(line, "the aperture is not positive"));
+        }
+        if element.radius != 0.0 && element.radius.abs() < element.aperture / 2.0 {
+            return Err(LoadError::parse(
+                line,
+                format!(
+                    "the aperture {} is wider than the surface with radius {}",
+                    element.aperture, element.radius
+                ),
+            ));
+        }
+        elements.push(element);
+    }
+
+    LensSystem::new(elements)
+        .ok_or_else(|| LoadError::Format("the lens prescription has no surfaces".to_string()))
+}
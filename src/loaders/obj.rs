@@ -0,0 +1,464 @@
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use super::LoadError;
+use crate::math::{
+    hittables::{HittableList, TriangleMesh},
+    materials::{Dielectric, Lambertian, Material, Metal},
+    Vec3,
+};
+
+/// Index of refraction used for transparent materials that don't specify `Ni`
+const DEFAULT_IOR: f64 = 1.5;
+
+/// Material used for faces that aren't preceded by any `usemtl` statement
+fn default_material() -> Arc<dyn Material> {
+    Arc::new(Lambertian::new(Vec3::new(0.8, 0.8, 0.8)))
+}
+
+/// Loads a Wavefront OBJ file. Material libraries referenced with `mtllib` are resolved relative
+/// to the directory of the OBJ file.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<HittableList, LoadError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    parse_obj(&source, path.parent().unwrap_or(Path::new("")))
+}
+
+/// Loads a Wavefront MTL material library
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<HashMap<String, Arc<dyn Material>>, LoadError> {
+    parse_mtl(&fs::read_to_string(path)?)
+}
+
+/// One corner of a face: indices into the position, texture coordinate and normal lists
+#[derive(Clone, Copy)]
+struct Corner {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Faces that belong to the same group and use the same material. Every chunk becomes its own
+/// `TriangleMesh`.
+struct Chunk {
+    material: Arc<dyn Material>,
+    faces: Vec<[Corner; 3]>,
+}
+
+impl Chunk {
+    fn new(material: Arc<dyn Material>) -> Self {
+        Chunk {
+            material,
+            faces: Vec::new(),
+        }
+    }
+}
+
+/// Parses the contents of an OBJ file. Supports positions (`v`), normals (`vn`), texture
+/// coordinates (`vt`), polygonal faces (`f`) which get triangulated, groups (`g`, `o`) and
+/// materials (`mtllib`, `usemtl`). Other statements are ignored.
+pub fn parse_obj(source: &str, base_dir: &Path) -> Result<HittableList, LoadError> {
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut tex_coords: Vec<(f64, f64)> = Vec::new();
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+
+    let mut chunks = Vec::new();
+    let mut current = Chunk::new(default_material());
+
+    for (idx, raw_line) in source.lines().enumerate() {
+        let line = idx + 1;
+        let mut tokens = strip_comment(raw_line).split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        match keyword {
+            "v" => positions.push(parse_vec3(&mut tokens, line)?),
+            "vn" => {
+                let normal = parse_vec3(&mut tokens, line)?;
+                // Zero normals cannot be normalized and would turn into NaN shading normals
+                let length = normal.length_squared();
+                if !(length > 0.0 && length.is_finite()) {
+                    return Err(LoadError::parse(line, "normal has zero length"));
+                }
+                normals.push(normal);
+            }
+            "vt" => {
+                let u = parse_f64(tokens.next(), line, "texture coordinate")?;
+                // `v` is optional and defaults to 0
+                let v = match tokens.next() {
+                    Some(v) => parse_f64(Some(v), line, "texture coordinate")?,
+                    None => 0.0,
+                };
+                tex_coords.push((u, v));
+            }
+            "f" => {
+                let corners = tokens
+                    .map(|token| {
+                        parse_corner(
+                            token,
+                            positions.len(),
+                            tex_coords.len(),
+                            normals.len(),
+                            line,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return Err(LoadError::parse(line, "face needs at least 3 vertices"));
+                }
+
+                let polygon: Vec<Vec3> = corners.iter().map(|c| positions[c.position]).collect();
+                for [a, b, c] in triangulate(&polygon) {
+                    current.faces.push([corners[a], corners[b], corners[c]]);
+                }
+            }
+            "g" | "o" => {
+                let material = current.material.clone();
+                chunks.push(std::mem::replace(&mut current, Chunk::new(material)));
+            }
+            "usemtl" => {
+                let name = tokens
+                    .next()
+                    .ok_or_else(|| LoadError::parse(line, "missing material name"))?;
+                let material = materials
+                    .get(name)
+                    .ok_or_else(|| LoadError::parse(line, format!("unknown material `{}`", name)))?
+                    .clone();
+                chunks.push(std::mem::replace(&mut current, Chunk::new(material)));
+            }
+            "mtllib" => {
+                for name in tokens {
+                    let library = load_mtl(base_dir.join(name)).map_err(|e| {
+                        LoadError::parse(
+                            line,
+                            format!("cannot load material library `{}`: {}", name, e),
+                        )
+                    })?;
+                    materials.extend(library);
+                }
+            }
+            _ => (),
+        }
+    }
+    chunks.push(current);
+
+    let mut world = HittableList::new();
+    for chunk in chunks.into_iter().filter(|c| !c.faces.is_empty()) {
+        world.add(Box::new(build_mesh(
+            chunk,
+            &positions,
+            &tex_coords,
+            &normals,
+        )));
+    }
+    Ok(world)
+}
+
+/// Builds a mesh from the chunk, only copying the vertices the chunk actually uses. OBJ indexes
+/// positions, texture coordinates and normals separately, so every distinct combination of them
+/// becomes one vertex. Normals and texture coordinates are only used if every corner has them.
+fn build_mesh(
+    chunk: Chunk,
+    positions: &[Vec3],
+    tex_coords: &[(f64, f64)],
+    normals: &[Vec3],
+) -> TriangleMesh {
+    let corners = || chunk.faces.iter().flatten();
+    let smooth = corners().all(|c| c.normal.is_some());
+    let textured = corners().all(|c| c.tex_coord.is_some());
+
+    let mut lookup: HashMap<(usize, Option<usize>, Option<usize>), usize> = HashMap::new();
+    let mut mesh_vertices = Vec::new();
+    let mut mesh_tex_coords = Vec::new();
+    let mut mesh_normals = Vec::new();
+
+    let indices = chunk
+        .faces
+        .iter()
+        .map(|face| {
+            face.map(|corner| {
+                let tex_coord = corner.tex_coord.filter(|_| textured);
+                let normal = corner.normal.filter(|_| smooth);
+                *lookup
+                    .entry((corner.position, tex_coord, normal))
+                    .or_insert_with(|| {
+                        mesh_vertices.push(positions[corner.position]);
+                        if let Some(t) = tex_coord {
+                            mesh_tex_coords.push(tex_coords[t]);
+                        }
+                        if let Some(n) = normal {
+                            mesh_normals.push(normals[n]);
+                        }
+                        mesh_vertices.len() - 1
+                    })
+            })
+        })
+        .collect();
+
+    TriangleMesh::with_attributes(
+        mesh_vertices,
+        smooth.then_some(mesh_normals),
+        textured.then_some(mesh_tex_coords),
+        indices,
+        chunk.material,
+    )
+}
+
+/// Parses the contents of an MTL file into materials keyed by their name.
+///
+/// MTL describes Phong-like materials, so they are mapped onto the closest material we have:
+/// - transparent materials (`d` < 1 or `Tr` > 0) become `Dielectric` with the index `Ni`,
+/// - materials whose specular color `Ks` is brighter than the diffuse `Kd` (or with `illum 3`)
+///   become `Metal` with the albedo `Ks` and the fuzziness derived from the exponent `Ns`,
+/// - everything else becomes `Lambertian` with the albedo `Kd`.
+pub fn parse_mtl(source: &str) -> Result<HashMap<String, Arc<dyn Material>>, LoadError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlParams)> = None;
+
+    for (idx, raw_line) in source.lines().enumerate() {
+        let line = idx + 1;
+        let mut tokens = strip_comment(raw_line).split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        if keyword == "newmtl" {
+            let name = tokens
+                .next()
+                .ok_or_else(|| LoadError::parse(line, "missing material name"))?;
+            if let Some((name, params)) = current.take() {
+                materials.insert(name, params.into_material());
+            }
+            current = Some((name.to_string(), MtlParams::default()));
+            continue;
+        }
+
+        let Some((_, params)) = current.as_mut() else {
+            return Err(LoadError::parse(
+                line,
+                format!("`{}` before any `newmtl`", keyword),
+            ));
+        };
+
+        match keyword {
+            "Kd" => params.diffuse = parse_color(&mut tokens, line)?,
+            "Ks" => params.specular = parse_color(&mut tokens, line)?,
+            "Ns" => params.shininess = parse_f64(tokens.next(), line, "specular exponent")?,
+            "Ni" => params.ior = Some(parse_f64(tokens.next(), line, "index of refraction")?),
+            "d" => params.dissolve = parse_f64(tokens.next(), line, "dissolve")?,
+            "Tr" => params.dissolve = 1.0 - parse_f64(tokens.next(), line, "transparency")?,
+            "illum" => params.illum = parse_f64(tokens.next(), line, "illumination model")? as u32,
+            _ => (),
+        }
+    }
+
+    if let Some((name, params)) = current {
+        materials.insert(name, params.into_material());
+    }
+    Ok(materials)
+}
+
+struct MtlParams {
+    diffuse: Vec3,
+    specular: Vec3,
+    shininess: f64,
+    ior: Option<f64>,
+    dissolve: f64,
+    illum: u32,
+}
+
+impl Default for MtlParams {
+    fn default() -> Self {
+        MtlParams {
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::zero(),
+            shininess: 0.0,
+            ior: None,
+            dissolve: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+impl MtlParams {
+    fn into_material(self) -> Arc<dyn Material> {
+        let max_component = |c: Vec3| c.x.max(c.y).max(c.z);
+
+        if self.dissolve < 1.0 {
+            Arc::new(Dielectric::new(self.ior.unwrap_or(DEFAULT_IOR)))
+        } else if self.illum == 3 || max_component(self.specular) > max_component(self.diffuse) {
+            // Approximate the roughness of a Blinn-Phong lobe with the exponent `Ns`
+            let fuzziness = (2.0 / (self.shininess.max(0.0) + 2.0)).sqrt();
+            Arc::new(Metal::new(self.specular, fuzziness))
+        } else {
+            Arc::new(Lambertian::new(self.diffuse))
+        }
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(idx) => &line[..idx],
+        None => line,
+    }
+}
+
+fn parse_f64(token: Option<&str>, line: usize, what: &str) -> Result<f64, LoadError> {
+    let token = token.ok_or_else(|| LoadError::parse(line, format!("missing {}", what)))?;
+    token
+        .parse()
+        .map_err(|_| LoadError::parse(line, format!("invalid {} `{}`", what, token)))
+}
+
+fn parse_vec3<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vec3, LoadError> {
+    Ok(Vec3::new(
+        parse_f64(tokens.next(), line, "coordinate")?,
+        parse_f64(tokens.next(), line, "coordinate")?,
+        parse_f64(tokens.next(), line, "coordinate")?,
+    ))
+}
+
+/// Parses an MTL color. A single value means a gray color.
+fn parse_color<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vec3, LoadError> {
+    let r = parse_f64(tokens.next(), line, "color component")?;
+    match tokens.next() {
+        None => Ok(Vec3::new(r, r, r)),
+        g => Ok(Vec3::new(
+            r,
+            parse_f64(g, line, "color component")?,
+            parse_f64(tokens.next(), line, "color component")?,
+        )),
+    }
+}
+
+/// Resolves a 1-based OBJ index into a 0-based one. Negative indices count back from the last
+/// element defined so far.
+fn parse_index(token: &str, count: usize, line: usize, what: &str) -> Result<usize, LoadError> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| LoadError::parse(line, format!("invalid {} index `{}`", what, token)))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(LoadError::parse(
+            line,
+            format!("{} index {} out of bounds", what, index),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+/// Parses a face corner in one of the forms `v`, `v/vt`, `v//vn` or `v/vt/vn`
+fn parse_corner(
+    token: &str,
+    position_count: usize,
+    tex_coord_count: usize,
+    normal_count: usize,
+    line: usize,
+) -> Result<Corner, LoadError> {
+    let mut parts = token.split('/');
+    let position = parse_index(parts.next().unwrap_or(""), position_count, line, "vertex")?;
+    let tex_coord = match parts.next().filter(|s| !s.is_empty()) {
+        Some(vt) => Some(parse_index(
+            vt,
+            tex_coord_count,
+            line,
+            "texture coordinate",
+        )?),
+        None => None,
+    };
+    let normal = match parts.next().filter(|s| !s.is_empty()) {
+        Some(vn) => Some(parse_index(vn, normal_count, line, "normal")?),
+        None => None,
+    };
+    Ok(Corner {
+        position,
+        tex_coord,
+        normal,
+    })
+}
+
+/// Splits a simple (possibly concave) planar polygon into triangles by ear clipping. Returns
+/// triangles as indices into `polygon`. Degenerate polygons fall back to a triangle fan.
+fn triangulate(polygon: &[Vec3]) -> Vec<[usize; 3]> {
+    let n = polygon.len();
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    // Newell's method gives a robust normal even for concave polygons
+    let mut normal = Vec3::zero();
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        normal = normal
+            + Vec3::new(
+                (a.y - b.y) * (a.z + b.z),
+                (a.z - b.z) * (a.x + b.x),
+                (a.x - b.x) * (a.y + b.y),
+            );
+    }
+    if normal.near_zero() {
+        return (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    }
+
+    let is_convex = |a: usize, b: usize, c: usize| {
+        (polygon[b] - polygon[a])
+            .cross(&(polygon[c] - polygon[b]))
+            .dot(&normal)
+            > 0.0
+    };
+    let inside = |p: Vec3, a: usize, b: usize, c: usize| {
+        [(a, b), (b, c), (c, a)].iter().all(|&(from, to)| {
+            (polygon[to] - polygon[from])
+                .cross(&(p - polygon[from]))
+                .dot(&normal)
+                >= 0.0
+        })
+    };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            is_convex(a, b, c)
+                && remaining
+                    .iter()
+                    .filter(|&&p| p != a && p != b && p != c)
+                    .all(|&p| !inside(polygon[p], a, b, c))
+        });
+
+        match ear {
+            Some(i) => {
+                triangles.push([
+                    remaining[(i + m - 1) % m],
+                    remaining[i],
+                    remaining[(i + 1) % m],
+                ]);
+                remaining.remove(i);
+            }
+            None => break,
+        }
+    }
+
+    // Whatever is left (a single triangle, or a degenerate remainder) is fanned out
+    for i in 1..remaining.len() - 1 {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+    triangles
+}
@@ -0,0 +1,84 @@
+use std::{fs, path::Path};
+
+use super::{pixel_count, LoadError};
+use crate::{math::Vec3, renderers::Image};
+
+/// Loads a Portable Float Map image
+pub fn load_pfm(path: impl AsRef<Path>) -> Result<Image, LoadError> {
+    parse_pfm(&fs::read(path)?)
+}
+
+/// Parses a Portable Float Map. `PF` files contain RGB pixels, `Pf` files grayscale pixels. A
+/// negative scale in the header means the floats are little-endian.
+pub fn parse_pfm(data: &[u8]) -> Result<Image, LoadError> {
+    // The header consists of three whitespace separated tokens after the magic number
+    let mut pos = 0;
+    let mut tokens = Vec::with_capacity(4);
+    while tokens.len() < 4 {
+        while data.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        let start = pos;
+        while data.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(LoadError::Format("unexpected end of header".to_string()));
+        }
+        tokens.push(String::from_utf8_lossy(&data[start..pos]).into_owned());
+    }
+    // A single whitespace character separates the header from the pixels
+    pos += 1;
+
+    let channels = match tokens[0].as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        magic => {
+            return Err(LoadError::Format(format!(
+                "invalid PFM magic number `{}`",
+                magic
+            )))
+        }
+    };
+    let parse_size = |token: &str| {
+        token
+            .parse::<usize>()
+            .map_err(|_| LoadError::Format(format!("invalid image size `{}`", token)))
+    };
+    let width = parse_size(&tokens[1])?;
+    let height = parse_size(&tokens[2])?;
+    let count = pixel_count(width, height)?;
+    let scale: f64 = tokens[3]
+        .parse()
+        .map_err(|_| LoadError::Format(format!("invalid scale `{}`", tokens[3])))?;
+    let little_endian = scale < 0.0;
+
+    let expected = count * channels * 4;
+    let raster = data
+        .get(pos..)
+        .and_then(|rest| rest.get(..expected))
+        .ok_or_else(|| LoadError::Format("unexpected end of pixel data".to_string()))?;
+    let floats: Vec<f64> = raster
+        .chunks_exact(4)
+        .map(|b| {
+            let bytes = [b[0], b[1], b[2], b[3]];
+            if little_endian {
+                f32::from_le_bytes(bytes) as f64
+            } else {
+                f32::from_be_bytes(bytes) as f64
+            }
+        })
+        .collect();
+
+    // Rows are stored from the bottom of the image to the top
+    let mut pixels = Vec::with_capacity(count);
+    for row in floats.chunks_exact(width * channels).rev() {
+        pixels.extend(row.chunks_exact(channels).map(|c| match c {
+            [r, g, b] => Vec3::new(*r, *g, *b),
+            [l] => Vec3::new(*l, *l, *l),
+            _ => unreachable!(),
+        }));
+    }
+
+    Ok(Image::from_pixels(width, height, pixels))
+}
@@ -0,0 +1,231 @@
+use std::{fs, path::Path};
+
+use miniz_oxide::inflate::{decompress_to_vec_zlib_with_limit, TINFLStatus};
+
+use super::{pixel_count, LoadError};
+use crate::renderers::{Color, Image};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Loads a PNG image
+pub fn load_png(path: impl AsRef<Path>) -> Result<Image, LoadError> {
+    parse_png(&fs::read(path)?)
+}
+
+/// Parses a non-interlaced PNG image of any color type and bit depth into linear space. 16-bit
+/// samples are reduced to 8 bits and the alpha channel is dropped.
+pub fn parse_png(data: &[u8]) -> Result<Image, LoadError> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err(format_error("missing PNG signature"));
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+
+    let mut pos = SIGNATURE.len();
+    loop {
+        let length = read_u32(data, pos)? as usize;
+        let kind = data
+            .get(pos + 4..pos + 8)
+            .ok_or_else(|| format_error("truncated chunk"))?;
+        let body = data
+            .get(pos + 8..pos + 8 + length)
+            .ok_or_else(|| format_error("truncated chunk"))?;
+        // The chunk is followed by its CRC
+        pos += 12 + length;
+
+        match kind {
+            b"IHDR" => header = Some(Header::parse(body)?),
+            b"PLTE" => palette = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => (),
+        }
+    }
+
+    let header = header.ok_or_else(|| format_error("missing IHDR chunk"))?;
+    // Every scanline starts with its filter type. Anything beyond that is not image data, so
+    // decompression stops there instead of following a zlib bomb.
+    let limit = (header.line_bytes() + 1) * header.height;
+    let raw =
+        decompress_to_vec_zlib_with_limit(&compressed, limit).map_err(|e| match e.status {
+            TINFLStatus::HasMoreOutput => format_error("image data is larger than the image"),
+            status => format_error(&format!("invalid compressed data: {:?}", status)),
+        })?;
+    let scanlines = unfilter(&header, &raw)?;
+
+    let mut image = Image::new(header.width, header.height);
+    for (y, line) in scanlines.chunks_exact(header.line_bytes()).enumerate() {
+        for x in 0..header.width {
+            image.set_pixel(x, y, header.pixel(line, x, palette)?.into());
+        }
+    }
+    Ok(image)
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(body: &[u8]) -> Result<Self, LoadError> {
+        if body.len() != 13 {
+            return Err(format_error("invalid IHDR chunk"));
+        }
+        let header = Header {
+            width: read_u32(body, 0)? as usize,
+            height: read_u32(body, 4)? as usize,
+            bit_depth: body[8],
+            color_type: body[9],
+        };
+        // Rejects empty images before any buffer is sized from the header
+        pixel_count(header.width, header.height)?;
+
+        let valid_depth = match header.color_type {
+            0 => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+            2 | 4 | 6 => matches!(header.bit_depth, 8 | 16),
+            _ => false,
+        };
+        if !valid_depth {
+            return Err(format_error(&format!(
+                "unsupported color type {} with bit depth {}",
+                header.color_type, header.bit_depth
+            )));
+        }
+        if body[12] != 0 {
+            return Err(format_error("interlaced PNGs are not supported"));
+        }
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn line_bytes(&self) -> usize {
+        (self.width * self.channels() * self.bit_depth as usize).div_ceil(8)
+    }
+
+    /// Bytes per complete pixel, rounded up to one byte, as used by the filters
+    fn filter_bytes(&self) -> usize {
+        (self.channels() * self.bit_depth as usize).div_ceil(8)
+    }
+
+    /// Reads the `i`-th sample of the scanline reduced to 8 bits (palette indices are not scaled)
+    fn sample(&self, line: &[u8], i: usize) -> u8 {
+        match self.bit_depth {
+            8 => line[i],
+            // The most significant byte comes first
+            16 => line[2 * i],
+            depth => {
+                let per_byte = 8 / depth as usize;
+                let shift = 8 - depth as usize * (i % per_byte + 1);
+                let value = (line[i / per_byte] >> shift) & ((1 << depth) - 1);
+                if self.color_type == 3 {
+                    value
+                } else {
+                    // Scale up so that the maximum value maps to 255
+                    (value as u32 * 255 / ((1 << depth) - 1)) as u8
+                }
+            }
+        }
+    }
+
+    fn pixel(&self, line: &[u8], x: usize, palette: &[u8]) -> Result<Color, LoadError> {
+        let first = x * self.channels();
+        Ok(match self.color_type {
+            2 | 6 => Color::new(
+                self.sample(line, first),
+                self.sample(line, first + 1),
+                self.sample(line, first + 2),
+            ),
+            3 => {
+                let idx = self.sample(line, first) as usize * 3;
+                let rgb = palette
+                    .get(idx..idx + 3)
+                    .ok_or_else(|| format_error("palette index out of bounds"))?;
+                Color::new(rgb[0], rgb[1], rgb[2])
+            }
+            _ => {
+                let gray = self.sample(line, first);
+                Color::new(gray, gray, gray)
+            }
+        })
+    }
+}
+
+/// Reverses the per-scanline filters and returns the scanlines without the filter type bytes
+fn unfilter(header: &Header, raw: &[u8]) -> Result<Vec<u8>, LoadError> {
+    let line_bytes = header.line_bytes();
+    let bpp = header.filter_bytes();
+    if raw.len() < (line_bytes + 1) * header.height {
+        return Err(format_error("not enough image data"));
+    }
+
+    let mut out = vec![0u8; line_bytes * header.height];
+    for y in 0..header.height {
+        let filter = raw[y * (line_bytes + 1)];
+        let src = &raw[y * (line_bytes + 1) + 1..(y + 1) * (line_bytes + 1)];
+        let (done, rest) = out.split_at_mut(y * line_bytes);
+        let prev = if y > 0 {
+            &done[(y - 1) * line_bytes..]
+        } else {
+            &[][..]
+        };
+        let line = &mut rest[..line_bytes];
+
+        for i in 0..line_bytes {
+            let a = if i >= bpp { line[i - bpp] } else { 0 };
+            let b = prev.get(i).copied().unwrap_or(0);
+            let c = if i >= bpp {
+                prev.get(i - bpp).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            let predictor = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(format_error(&format!("invalid filter type {}", filter))),
+            };
+            line[i] = src[i].wrapping_add(predictor);
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Result<u32, LoadError> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format_error("unexpected end of file"))
+}
+
+fn format_error(message: &str) -> LoadError {
+    LoadError::Format(message.to_string())
+}
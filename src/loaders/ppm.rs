@@ -0,0 +1,113 @@
+use std::{fs, path::Path};
+
+use super::{pixel_count, LoadError};
+use crate::renderers::{Color, Image};
+
+/// Loads a PPM image in the ASCII (`P3`) or the binary (`P6`) format
+pub fn load_ppm(path: impl AsRef<Path>) -> Result<Image, LoadError> {
+    parse_ppm(&fs::read(path)?)
+}
+
+/// Parses a `P3` or `P6` PPM image into linear space. Samples with a maximum value other than 255
+/// are rescaled to 8 bits.
+pub fn parse_ppm(data: &[u8]) -> Result<Image, LoadError> {
+    let mut pos = 0;
+    let magic = next_token(data, &mut pos)?;
+    let binary = match magic {
+        b"P3" => false,
+        b"P6" => true,
+        _ => {
+            return Err(LoadError::Format(format!(
+                "unsupported PPM magic number `{}`",
+                String::from_utf8_lossy(magic)
+            )))
+        }
+    };
+
+    let width = next_number(data, &mut pos)? as usize;
+    let height = next_number(data, &mut pos)? as usize;
+    let max_value = next_number(data, &mut pos)?;
+    if max_value == 0 || max_value > 65535 {
+        return Err(LoadError::Format(format!(
+            "invalid maximum value {}",
+            max_value
+        )));
+    }
+
+    // Rejects empty and oversized images before any buffer is sized from the header
+    let sample_count = pixel_count(width, height)? * 3;
+    let samples: Vec<u32> = if binary {
+        // A single whitespace character separates the header from the raster
+        pos += 1;
+        let bytes_per_sample = if max_value < 256 { 1 } else { 2 };
+        let raster = sample_count
+            .checked_mul(bytes_per_sample)
+            .and_then(|length| data.get(pos..)?.get(..length))
+            .ok_or_else(|| LoadError::Format("unexpected end of pixel data".to_string()))?;
+        if bytes_per_sample == 1 {
+            raster.iter().map(|&b| b as u32).collect()
+        } else {
+            raster
+                .chunks_exact(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+                .collect()
+        }
+    } else {
+        (0..sample_count)
+            .map(|_| next_number(data, &mut pos))
+            .collect::<Result<_, _>>()?
+    };
+
+    let to_byte = |sample: u32| -> Result<u8, LoadError> {
+        if sample > max_value {
+            return Err(LoadError::Format(format!(
+                "sample {} exceeds the maximum value {}",
+                sample, max_value
+            )));
+        }
+        Ok(((sample * 255 + max_value / 2) / max_value) as u8)
+    };
+
+    let mut image = Image::new(width, height);
+    for (idx, rgb) in samples.chunks_exact(3).enumerate() {
+        let color = Color::new(to_byte(rgb[0])?, to_byte(rgb[1])?, to_byte(rgb[2])?);
+        image.set_pixel(idx % width, idx / width, color.into());
+    }
+    Ok(image)
+}
+
+/// Returns the next whitespace separated token, skipping `#` comments
+fn next_token<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a [u8], LoadError> {
+    loop {
+        match data.get(*pos) {
+            Some(b) if b.is_ascii_whitespace() => *pos += 1,
+            Some(b'#') => {
+                while data.get(*pos).is_some_and(|&b| b != b'\n') {
+                    *pos += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+    let start = *pos;
+    while data.get(*pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+    if start == *pos {
+        return Err(LoadError::Format("unexpected end of file".to_string()));
+    }
+    Ok(&data[start..*pos])
+}
+
+fn next_number(data: &[u8], pos: &mut usize) -> Result<u32, LoadError> {
+    let token = next_token(data, pos)?;
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .ok_or_else(|| {
+            LoadError::Format(format!(
+                "invalid number `{}`",
+                String::from_utf8_lossy(token)
+            ))
+        })
+}
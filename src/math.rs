@@ -1,17 +1,23 @@
 use std::{
     f64::consts::PI,
-    ops::{Add, Div, Mul, Sub},
-    rc::Rc,
+    ops::{Add, Div, Index, Mul, Neg, Sub},
+    sync::Arc,
 };
 
+use aabb::Aabb;
+use color::{srgb_decode, srgb_encode};
 use interval::Interval;
 use materials::Material;
 use rand::Rng;
 
 use crate::renderers::{self, Color};
+pub mod aabb;
+pub mod backgrounds;
+pub mod color;
 pub mod hittables;
 pub mod interval;
 pub mod materials;
+pub mod textures;
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
@@ -25,7 +31,7 @@ pub fn reflect(incoming: &Vec3, normal: &Vec3) -> Vec3 {
 /// Refract the incoming ray according to Snell's law. If the ray cannot be refracted,
 /// it is reflected
 pub fn refract(incoming: &Vec3, normal: &Vec3, refraction_ratio: f64) -> Vec3 {
-    let cos_theta = (-1.0 * incoming.dot(normal)).min(1.0);
+    let cos_theta = (-incoming.dot(normal)).min(1.0);
     let vec_out_perpendicular = refraction_ratio * (*incoming + cos_theta * *normal);
     let vec_out_parallel = -((1.0 - vec_out_perpendicular.length_squared()).abs().sqrt()) * *normal;
     vec_out_perpendicular + vec_out_parallel
@@ -126,7 +132,7 @@ impl Vec3 {
         if on_unit_sphere.dot(&normal.unit()) >= 0.0 {
             on_unit_sphere
         } else {
-            -1.0 * on_unit_sphere
+            -on_unit_sphere
         }
     }
 
@@ -158,6 +164,15 @@ impl Sub for Vec3 {
     }
 }
 
+// Vector negation
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Self::Output {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
 // Scalar multiplication (Vec3 * f64)
 impl Mul<f64> for Vec3 {
     type Output = Vec3;
@@ -221,20 +236,27 @@ impl Mul<Vec3> for Vec3 {
     }
 }
 
-fn linear_to_gamma(x: f64) -> f64 {
-    if x > 0.0 {
-        x.sqrt()
-    } else {
-        0.0
+// Component access by axis index (0 is `x`, 1 is `y`, 2 is `z`)
+impl Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index {} out of bounds", index),
+        }
     }
 }
 
+/// Encodes a linear sRGB color with the sRGB transfer function and quantizes it to 8 bits
 impl From<Vec3> for renderers::Color {
     fn from(val: Vec3) -> Self {
         let interval = Interval::new(0.0, 0.9999);
-        let r = linear_to_gamma(val.x);
-        let g = linear_to_gamma(val.y);
-        let b = linear_to_gamma(val.z);
+        let r = srgb_encode(val.x);
+        let g = srgb_encode(val.y);
+        let b = srgb_encode(val.z);
         Color::new(
             (interval.clamp(r) * 256.0) as u8,
             (interval.clamp(g) * 256.0) as u8,
@@ -243,6 +265,17 @@ impl From<Vec3> for renderers::Color {
     }
 }
 
+/// Converts an 8-bit sRGB encoded color back to linear space
+impl From<renderers::Color> for Vec3 {
+    fn from(c: renderers::Color) -> Self {
+        Vec3::new(
+            srgb_decode(c.r as f64 / 255.0),
+            srgb_decode(c.g as f64 / 255.0),
+            srgb_decode(c.b as f64 / 255.0),
+        )
+    }
+}
+
 /// The `Ray` struct represents a ray with a certain origin and direction.
 /// It also implements the function `at` that can calculate any point along the ray at a certain
 /// scalar `t`
@@ -268,8 +301,15 @@ pub struct HitRecord {
     pub t: f64,
     /// Tracks whether we hit the front face of the object
     pub front_face: bool,
+    /// Surface coordinates of the hit point used for texture lookups, usually in `[0, 1]`
+    pub u: f64,
+    pub v: f64,
+    /// Partial derivatives of the surface point with respect to `u` and `v`. They lie in the
+    /// tangent plane and are not normalized.
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     /// The material of the hit object
-    pub mat: Rc<dyn Material>,
+    pub mat: Arc<dyn Material>,
 }
 
 impl HitRecord {
@@ -282,9 +322,29 @@ impl HitRecord {
         if self.front_face {
             self.normal = *outward_normal;
         } else {
-            self.normal = -1.0 * (*outward_normal);
+            self.normal = -*outward_normal;
         }
     }
+
+    /// Returns an orthonormal basis `(tangent, bitangent, normal)` at the hit point. The tangent
+    /// follows the direction of increasing `u`, which is what normal maps expect.
+    pub fn tangent_frame(&self) -> (Vec3, Vec3, Vec3) {
+        let n = self.normal;
+        // Gram-Schmidt: remove the part of `dpdu` that isn't in the tangent plane
+        let projected = self.dpdu - self.dpdu.dot(&n) * n;
+        let tangent = if projected.near_zero() {
+            // The parameterization is degenerate here (e.g. at the poles), so any tangent will do
+            let helper = if n.x.abs() > 0.9 {
+                Vec3::new(0.0, 1.0, 0.0)
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+            n.cross(&helper).unit()
+        } else {
+            projected.unit()
+        };
+        (tangent, n.cross(&tangent), n)
+    }
 }
 
 pub enum HitResult {
@@ -292,6 +352,8 @@ pub enum HitResult {
     Hit(HitRecord),
 }
 
-pub trait Hittable {
+pub trait Hittable: Send + Sync {
     fn hit(&self, r: &Ray, ray_t: Interval) -> HitResult;
+    /// The axis-aligned box that fully encloses the object
+    fn bounding_box(&self) -> Aabb;
 }
@@ -0,0 +1,125 @@
+use super::{interval::Interval, Ray, Vec3};
+
+/// Axis-aligned bounding box described by one interval per axis
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+/// Boxes thinner than this are padded so that rays never miss flat objects (e.g. triangles lying
+/// in an axis plane) because of floating point errors.
+const MIN_THICKNESS: f64 = 0.0001;
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        let mut bbox = Aabb { x, y, z };
+        bbox.pad_to_minimums();
+        bbox
+    }
+
+    /// A box that contains nothing
+    pub fn empty() -> Self {
+        Aabb {
+            x: Interval::empty(),
+            y: Interval::empty(),
+            z: Interval::empty(),
+        }
+    }
+
+    /// Constructs the box with `a` and `b` as its opposite corners
+    pub fn from_points(a: Vec3, b: Vec3) -> Self {
+        Aabb::new(
+            Interval::new(a.x.min(b.x), a.x.max(b.x)),
+            Interval::new(a.y.min(b.y), a.y.max(b.y)),
+            Interval::new(a.z.min(b.z), a.z.max(b.z)),
+        )
+    }
+
+    /// The smallest box that contains both `a` and `b`
+    pub fn enclosing(a: &Aabb, b: &Aabb) -> Self {
+        Aabb {
+            x: Interval::enclosing(a.x, b.x),
+            y: Interval::enclosing(a.y, b.y),
+            z: Interval::enclosing(a.z, b.z),
+        }
+    }
+
+    /// Returns the interval of the box along the axis `n` (0 is `x`, 1 is `y`, 2 is `z`)
+    pub fn axis_interval(&self, n: usize) -> Interval {
+        match n {
+            1 => self.y,
+            2 => self.z,
+            _ => self.x,
+        }
+    }
+
+    /// Index of the axis along which the box is the longest
+    pub fn longest_axis(&self) -> usize {
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() {
+                0
+            } else {
+                2
+            }
+        } else if self.y.size() > self.z.size() {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.size() < 0.0 || self.y.size() < 0.0 || self.z.size() < 0.0
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        Vec3::new(
+            (self.x.min + self.x.max) / 2.0,
+            (self.y.min + self.y.max) / 2.0,
+            (self.z.min + self.z.max) / 2.0,
+        )
+    }
+
+    /// Surface area of the box, used for the surface area heuristic when building the BVH
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        2.0 * (dx * dy + dy * dz + dz * dx)
+    }
+
+    /// Checks whether the ray hits the box within `ray_t` using the slab method
+    pub fn hit(&self, r: &Ray, mut ray_t: Interval) -> bool {
+        for axis in 0..3 {
+            let ax = self.axis_interval(axis);
+            let adinv = 1.0 / r.dir[axis];
+
+            let t0 = (ax.min - r.origin[axis]) * adinv;
+            let t1 = (ax.max - r.origin[axis]) * adinv;
+
+            let (t_near, t_far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            ray_t.min = ray_t.min.max(t_near);
+            ray_t.max = ray_t.max.min(t_far);
+
+            if ray_t.max <= ray_t.min {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn pad_to_minimums(&mut self) {
+        if self.x.size() < MIN_THICKNESS {
+            self.x = self.x.expand(MIN_THICKNESS);
+        }
+        if self.y.size() < MIN_THICKNESS {
+            self.y = self.y.expand(MIN_THICKNESS);
+        }
+        if self.z.size() < MIN_THICKNESS {
+            self.z = self.z.expand(MIN_THICKNESS);
+        }
+    }
+}
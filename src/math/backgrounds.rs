@@ -0,0 +1,150 @@
+use std::{f64::consts::PI, path::Path};
+
+use super::{degrees_to_radians, Ray, Vec3};
+use crate::{
+    loaders::{self, LoadError},
+    renderers::Image,
+};
+
+/// What a ray sees when it doesn't hit any object in the scene
+pub trait Background: Send + Sync {
+    /// The light coming from the direction of the ray
+    fn color(&self, r: &Ray) -> Vec3;
+}
+
+/// Every closure taking a ray and returning a color can be used as a background
+impl<F> Background for F
+where
+    F: Fn(&Ray) -> Vec3 + Send + Sync,
+{
+    fn color(&self, r: &Ray) -> Vec3 {
+        self(r)
+    }
+}
+
+/// A background of a single color in all directions
+pub struct SolidBackground {
+    color: Vec3,
+}
+
+impl SolidBackground {
+    pub fn new(color: Vec3) -> Self {
+        Self { color }
+    }
+
+    /// A background that emits no light, for enclosed scenes lit only by emissive materials
+    pub fn black() -> Self {
+        Self::new(Vec3::zero())
+    }
+}
+
+impl Background for SolidBackground {
+    fn color(&self, _r: &Ray) -> Vec3 {
+        self.color
+    }
+}
+
+/// Linearly blends between two colors based on the height of the ray direction
+pub struct VerticalGradient {
+    /// The color when looking straight down
+    bottom: Vec3,
+    /// The color when looking straight up
+    top: Vec3,
+}
+
+impl VerticalGradient {
+    pub fn new(bottom: Vec3, top: Vec3) -> Self {
+        Self { bottom, top }
+    }
+
+    /// White looking down and light blue looking up, blending to a pale blue at the horizon
+    pub fn sky() -> Self {
+        Self::new(Vec3::new(1.0, 1.0, 1.0), Vec3::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Default for VerticalGradient {
+    fn default() -> Self {
+        Self::sky()
+    }
+}
+
+impl Background for VerticalGradient {
+    fn color(&self, r: &Ray) -> Vec3 {
+        let unit_dir = r.dir.unit();
+        let a = 0.5 * (unit_dir.y + 1.0);
+
+        (1.0 - a) * self.bottom + a * self.top
+    }
+}
+
+/// Image based lighting from an equirectangular (latitude-longitude) panorama. The center of the
+/// image is in the `-z` direction and the top row is straight up.
+pub struct EnvironmentMap {
+    image: Image,
+    /// Rotation around the vertical axis in radians
+    rotation: f64,
+}
+
+impl EnvironmentMap {
+    /// An empty image gives a black background
+    pub fn new(image: Image) -> Self {
+        Self {
+            image,
+            rotation: 0.0,
+        }
+    }
+
+    /// Loads the panorama from a Radiance `.hdr` or a `.pfm` file, based on the extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let image = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("pfm") => loaders::load_pfm(path)?,
+            _ => loaders::load_hdr(path)?,
+        };
+        Ok(Self::new(image))
+    }
+
+    /// Rotates the map around the vertical axis. Positive angles turn it counter-clockwise when
+    /// viewed from above.
+    pub fn set_rotation(&mut self, degrees: f64) {
+        self.rotation = degrees_to_radians(degrees);
+    }
+
+    /// Bilinearly interpolates the image at the continuous pixel coordinates `(x, y)`. The image
+    /// wraps around horizontally and is clamped at the poles.
+    fn sample(&self, x: f64, y: f64) -> Vec3 {
+        let (width, height) = (self.image.width, self.image.height);
+        let x = x - 0.5;
+        let y = (y - 0.5).clamp(0.0, (height - 1) as f64);
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let (tx, ty) = (x - x0, y - y0);
+
+        let wrap = |x: f64| (x as i64).rem_euclid(width as i64) as usize;
+        let (x0, x1) = (wrap(x0), wrap(x0 + 1.0));
+        let (y0, y1) = (y0 as usize, (y0 as usize + 1).min(height - 1));
+
+        let pixel = |x, y| *self.image.get_pixel(x, y);
+        let top = (1.0 - tx) * pixel(x0, y0) + tx * pixel(x1, y0);
+        let bottom = (1.0 - tx) * pixel(x0, y1) + tx * pixel(x1, y1);
+        (1.0 - ty) * top + ty * bottom
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, r: &Ray) -> Vec3 {
+        if self.image.width == 0 || self.image.height == 0 {
+            return Vec3::zero();
+        }
+        let dir = r.dir.unit();
+        // Azimuth measured from `-z` towards `+x`, and the polar angle measured from `+y`
+        let phi = dir.x.atan2(-dir.z) - self.rotation;
+        let theta = dir.y.clamp(-1.0, 1.0).acos();
+
+        let u = (phi / (2.0 * PI) + 0.5).rem_euclid(1.0);
+        let v = theta / PI;
+        self.sample(u * self.image.width as f64, v * self.image.height as f64)
+    }
+}
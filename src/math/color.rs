@@ -0,0 +1,175 @@
+use super::Vec3;
+
+/// Encodes a linear value with the piecewise sRGB transfer function
+pub fn srgb_encode(x: f64) -> f64 {
+    if x <= 0.0031308 {
+        12.92 * x.max(0.0)
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Inverse of `srgb_encode`
+pub fn srgb_decode(x: f64) -> f64 {
+    if x <= 0.04045 {
+        x.max(0.0) / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Linear RGB color spaces the renderer can work in. They only differ in their primaries and
+/// white point, the transfer function is applied separately when colors are read or written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// sRGB primaries, which are the same as the Rec. 709 ones
+    #[default]
+    Srgb,
+    /// The wide gamut of Rec. 2020 (UHDTV)
+    Rec2020,
+    /// The AP1 primaries of ACES used for rendering and compositing
+    AcesCg,
+}
+
+type Matrix = [[f64; 3]; 3];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// CIE xy chromaticity of the D65 white point, which is also the white of the XYZ space we
+/// convert through
+const D65: (f64, f64) = (0.3127, 0.3290);
+/// CIE xy chromaticity of the white point of ACES (close to D60)
+const ACES_WHITE: (f64, f64) = (0.32168, 0.33767);
+
+/// Bradford cone response matrix used for chromatic adaptation
+const BRADFORD: Matrix = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+impl ColorSpace {
+    /// CIE xy chromaticities of the red, green and blue primaries
+    fn primaries(&self) -> [(f64, f64); 3] {
+        match self {
+            ColorSpace::Srgb => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)],
+            ColorSpace::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)],
+            ColorSpace::AcesCg => [(0.713, 0.293), (0.165, 0.830), (0.128, 0.044)],
+        }
+    }
+
+    fn white_point(&self) -> (f64, f64) {
+        match self {
+            ColorSpace::Srgb | ColorSpace::Rec2020 => D65,
+            ColorSpace::AcesCg => ACES_WHITE,
+        }
+    }
+
+    /// The matrix taking linear RGB in this space to CIE XYZ relative to D65
+    pub fn to_xyz(&self) -> Matrix {
+        // The columns are the XYZ coordinates of the primaries, scaled so that RGB (1, 1, 1)
+        // lands on the white point
+        let primaries = self.primaries().map(xy_to_xyz);
+        let unscaled = transpose(primaries);
+        let scale = mul_vec(&inverse(&unscaled), xy_to_xyz(self.white_point()));
+        let mut m = unscaled;
+        for row in m.iter_mut() {
+            for (value, s) in row.iter_mut().zip(scale) {
+                *value *= s;
+            }
+        }
+
+        if self.white_point() == D65 {
+            m
+        } else {
+            mul(&adaptation(self.white_point(), D65), &m)
+        }
+    }
+
+    /// The matrix taking CIE XYZ relative to D65 to linear RGB in this space
+    pub fn from_xyz(&self) -> Matrix {
+        inverse(&self.to_xyz())
+    }
+
+    /// The matrix converting linear RGB in this space into linear RGB in `target`
+    pub fn conversion_to(&self, target: ColorSpace) -> Matrix {
+        if *self == target {
+            return IDENTITY;
+        }
+        mul(&target.from_xyz(), &self.to_xyz())
+    }
+
+    /// Converts a linear color in this space into `target`
+    pub fn convert(&self, c: Vec3, target: ColorSpace) -> Vec3 {
+        if *self == target {
+            return c;
+        }
+        let [x, y, z] = mul_vec(&self.conversion_to(target), [c.x, c.y, c.z]);
+        Vec3::new(x, y, z)
+    }
+}
+
+/// XYZ coordinates of a chromaticity with a luminance of 1
+fn xy_to_xyz((x, y): (f64, f64)) -> [f64; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+/// Bradford chromatic adaptation from the white point `from` to `to`
+fn adaptation(from: (f64, f64), to: (f64, f64)) -> Matrix {
+    let source = mul_vec(&BRADFORD, xy_to_xyz(from));
+    let target = mul_vec(&BRADFORD, xy_to_xyz(to));
+    let mut scale = [[0.0; 3]; 3];
+    for i in 0..3 {
+        scale[i][i] = target[i] / source[i];
+    }
+    mul(&inverse(&BRADFORD), &mul(&scale, &BRADFORD))
+}
+
+fn transpose(m: [[f64; 3]; 3]) -> Matrix {
+    let mut t = [[0.0; 3]; 3];
+    for (i, row) in m.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            t[j][i] = *value;
+        }
+    }
+    t
+}
+
+fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn mul_vec(m: &Matrix, v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Inverts the matrix with the adjugate. The matrices we deal with are always well conditioned.
+fn inverse(m: &Matrix) -> Matrix {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let adjugate = [
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ];
+    let det = m[0][0] * adjugate[0][0] + m[0][1] * adjugate[1][0] + m[0][2] * adjugate[2][0];
+    adjugate.map(|row| row.map(|value| value / det))
+}
@@ -1,6 +1,14 @@
-use std::rc::Rc;
+use std::{f64::consts::PI, sync::Arc};
 
-use super::{interval::Interval, materials::Material, HitRecord, HitResult, Hittable, Ray, Vec3};
+use super::{
+    aabb::Aabb, interval::Interval, materials::Material, HitRecord, HitResult, Hittable, Ray, Vec3,
+};
+
+pub mod bvh;
+pub mod triangle;
+
+pub use bvh::BvhNode;
+pub use triangle::{Triangle, TriangleMesh};
 
 pub struct HittableList {
     pub objects: Vec<Box<dyn Hittable>>,
@@ -26,39 +34,74 @@ impl Default for HittableList {
 
 impl Hittable for HittableList {
     fn hit(&self, r: &Ray, ray_t: Interval) -> HitResult {
-        let mut res = HitResult::NoHit;
-        let mut closest_so_far = ray_t.max;
-
-        for obj in &self.objects {
-            match obj.hit(r, Interval::new(ray_t.min, closest_so_far)) {
-                HitResult::NoHit => (),
-                HitResult::Hit(rec) => {
-                    if closest_so_far > rec.t {
-                        closest_so_far = rec.t;
-                        res = HitResult::Hit(rec);
-                    }
+        closest_hit(&self.objects, r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.objects.iter().fold(Aabb::empty(), |bbox, obj| {
+            Aabb::enclosing(&bbox, &obj.bounding_box())
+        })
+    }
+}
+
+/// Tests the ray against every object and returns the hit closest to the ray origin
+fn closest_hit(objects: &[Box<dyn Hittable>], r: &Ray, ray_t: Interval) -> HitResult {
+    let mut res = HitResult::NoHit;
+    let mut closest_so_far = ray_t.max;
+
+    for obj in objects {
+        match obj.hit(r, Interval::new(ray_t.min, closest_so_far)) {
+            HitResult::NoHit => (),
+            HitResult::Hit(rec) => {
+                if closest_so_far > rec.t {
+                    closest_so_far = rec.t;
+                    res = HitResult::Hit(rec);
                 }
             }
         }
-
-        res
     }
+
+    res
 }
 
 pub struct Sphere {
     center: Vec3,
     radius: f64,
-    mat: Rc<dyn Material>,
+    mat: Arc<dyn Material>,
 }
 
 impl Sphere {
-    pub fn new(center: Vec3, radius: f64, mat: Rc<dyn Material>) -> Self {
+    pub fn new(center: Vec3, radius: f64, mat: Arc<dyn Material>) -> Self {
         Sphere {
             center,
             radius,
             mat,
         }
     }
+
+    /// Maps a point on the unit sphere to texture coordinates. `u` goes around the `y` axis
+    /// starting at `-x`, `v` goes from the bottom (`y = -1`) to the top (`y = 1`).
+    fn sphere_uv(p: &Vec3) -> (f64, f64) {
+        let theta = (-p.y).clamp(-1.0, 1.0).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    /// Derivatives of the parameterization from `sphere_uv`, where a point on the sphere is
+    /// `radius * (sin(theta) cos(phi), -cos(theta), -sin(theta) sin(phi))` with
+    /// `phi = 2 pi u - pi` and `theta = pi v`
+    fn sphere_derivatives(&self, u: f64, v: f64) -> (Vec3, Vec3) {
+        let phi = 2.0 * PI * u - PI;
+        let theta = PI * v;
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let (sin_theta, cos_theta) = theta.sin_cos();
+
+        let dpdu =
+            2.0 * PI * self.radius * Vec3::new(-sin_theta * sin_phi, 0.0, -sin_theta * cos_phi);
+        let dpdv =
+            PI * self.radius * Vec3::new(cos_theta * cos_phi, sin_theta, -cos_theta * sin_phi);
+        (dpdu, dpdv)
+    }
 }
 
 impl Hittable for Sphere {
@@ -84,17 +127,28 @@ impl Hittable for Sphere {
             }
         }
 
+        let outward_normal = (r.at(root) - self.center) / self.radius;
+        let (u, v) = Sphere::sphere_uv(&outward_normal);
+        let (dpdu, dpdv) = self.sphere_derivatives(u, v);
         let mut record = HitRecord {
             t: root,
             point: r.at(root),
-            normal: (r.at(root) - self.center) / self.radius,
+            normal: outward_normal,
             front_face: false,
+            u,
+            v,
+            dpdu,
+            dpdv,
             mat: self.mat.clone(),
         };
 
-        let outward_normal = (record.point - self.center) / self.radius;
         record.set_face_normal(r, &outward_normal);
 
         HitResult::Hit(record)
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let rvec = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::from_points(self.center - rvec, self.center + rvec)
+    }
 }
@@ -0,0 +1,185 @@
+use super::{closest_hit, HittableList};
+use crate::math::{aabb::Aabb, interval::Interval, HitResult, Hittable, Ray, Vec3};
+
+/// Number of buckets the centroids are binned into when evaluating the surface area heuristic
+const SAH_BUCKETS: usize = 12;
+/// Nodes with at most this many objects may become leaves
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of traversing a node relative to the cost of intersecting one object
+const TRAVERSAL_COST: f64 = 0.125;
+
+/// A node of a bounding volume hierarchy. The hierarchy is built once from a `HittableList` and
+/// lets us skip whole groups of objects whose bounding box the ray misses.
+pub struct BvhNode {
+    bbox: Aabb,
+    children: BvhChildren,
+}
+
+enum BvhChildren {
+    Leaf(Vec<Box<dyn Hittable>>),
+    Split(Box<BvhNode>, Box<BvhNode>),
+}
+
+/// An object together with its cached bounding box and centroid used during construction
+struct BvhPrimitive {
+    object: Box<dyn Hittable>,
+    bbox: Aabb,
+    centroid: Vec3,
+}
+
+#[derive(Clone, Copy)]
+struct SahBucket {
+    count: usize,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(list: HittableList) -> Self {
+        let primitives = list
+            .objects
+            .into_iter()
+            .map(|object| {
+                let bbox = object.bounding_box();
+                BvhPrimitive {
+                    object,
+                    centroid: bbox.centroid(),
+                    bbox,
+                }
+            })
+            .collect();
+        BvhNode::build(primitives)
+    }
+
+    fn leaf(primitives: Vec<BvhPrimitive>, bbox: Aabb) -> Self {
+        BvhNode {
+            bbox,
+            children: BvhChildren::Leaf(primitives.into_iter().map(|p| p.object).collect()),
+        }
+    }
+
+    fn split(left: Vec<BvhPrimitive>, right: Vec<BvhPrimitive>, bbox: Aabb) -> Self {
+        BvhNode {
+            bbox,
+            children: BvhChildren::Split(
+                Box::new(BvhNode::build(left)),
+                Box::new(BvhNode::build(right)),
+            ),
+        }
+    }
+
+    fn build(mut primitives: Vec<BvhPrimitive>) -> Self {
+        let bbox = primitives
+            .iter()
+            .fold(Aabb::empty(), |bbox, p| Aabb::enclosing(&bbox, &p.bbox));
+        let count = primitives.len();
+
+        if count <= 1 {
+            return BvhNode::leaf(primitives, bbox);
+        }
+
+        // We split along the axis on which the centroids are spread out the most
+        let centroid_bounds = primitives.iter().fold(Aabb::empty(), |bounds, p| {
+            let point = Aabb {
+                x: Interval::new(p.centroid.x, p.centroid.x),
+                y: Interval::new(p.centroid.y, p.centroid.y),
+                z: Interval::new(p.centroid.z, p.centroid.z),
+            };
+            Aabb::enclosing(&bounds, &point)
+        });
+        let axis = centroid_bounds.longest_axis();
+        let extent = centroid_bounds.axis_interval(axis);
+
+        // All of the centroids are in the same spot, so no split can separate them
+        if extent.size() <= 0.0 {
+            if count <= MAX_LEAF_SIZE {
+                return BvhNode::leaf(primitives, bbox);
+            }
+            let right = primitives.split_off(count / 2);
+            return BvhNode::split(primitives, right, bbox);
+        }
+
+        let bucket_of = |p: &BvhPrimitive| {
+            let offset = (p.centroid[axis] - extent.min) / extent.size();
+            ((offset * SAH_BUCKETS as f64) as usize).min(SAH_BUCKETS - 1)
+        };
+
+        let mut buckets = [SahBucket {
+            count: 0,
+            bbox: Aabb::empty(),
+        }; SAH_BUCKETS];
+        for p in &primitives {
+            let bucket = &mut buckets[bucket_of(p)];
+            bucket.count += 1;
+            bucket.bbox = Aabb::enclosing(&bucket.bbox, &p.bbox);
+        }
+
+        // Evaluate the cost of splitting after each bucket. The probability of a ray hitting a
+        // child is proportional to the ratio of the surface areas of the child and the parent.
+        let mut best_split = 0;
+        let mut best_cost = f64::INFINITY;
+        for split in 0..SAH_BUCKETS - 1 {
+            let (left, right) = buckets.split_at(split + 1);
+            let merge = |bs: &[SahBucket]| {
+                bs.iter().fold((0, Aabb::empty()), |(count, bbox), b| {
+                    (count + b.count, Aabb::enclosing(&bbox, &b.bbox))
+                })
+            };
+            let (left_count, left_bbox) = merge(left);
+            let (right_count, right_bbox) = merge(right);
+
+            let cost = TRAVERSAL_COST
+                + (left_count as f64 * left_bbox.surface_area()
+                    + right_count as f64 * right_bbox.surface_area())
+                    / bbox.surface_area();
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        // Intersecting every object in a leaf costs `count`
+        if count <= MAX_LEAF_SIZE && count as f64 <= best_cost {
+            return BvhNode::leaf(primitives, bbox);
+        }
+
+        // The first and the last bucket are never empty, so both sides get at least one object
+        let (left, right) = primitives
+            .into_iter()
+            .partition(|p| bucket_of(p) <= best_split);
+        BvhNode::split(left, right, bbox)
+    }
+}
+
+impl From<HittableList> for BvhNode {
+    fn from(list: HittableList) -> Self {
+        BvhNode::new(list)
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> HitResult {
+        if !self.bbox.hit(r, ray_t) {
+            return HitResult::NoHit;
+        }
+
+        match &self.children {
+            BvhChildren::Leaf(objects) => closest_hit(objects, r, ray_t),
+            BvhChildren::Split(left, right) => {
+                let left_hit = left.hit(r, ray_t);
+                // The right child only has to find hits closer than the one on the left
+                let closest_so_far = match &left_hit {
+                    HitResult::Hit(rec) => rec.t,
+                    HitResult::NoHit => ray_t.max,
+                };
+                match right.hit(r, Interval::new(ray_t.min, closest_so_far)) {
+                    HitResult::NoHit => left_hit,
+                    right_hit => right_hit,
+                }
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
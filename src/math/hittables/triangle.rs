@@ -0,0 +1,366 @@
+use std::sync::Arc;
+
+use super::{BvhNode, HittableList};
+use crate::math::{
+    aabb::Aabb, interval::Interval, materials::Material, HitRecord, HitResult, Hittable, Ray, Vec3,
+};
+
+/// Rays that are this close to parallel with the triangle plane are treated as misses
+const PARALLEL_EPSILON: f64 = 1e-12;
+
+/// Result of the Möller–Trumbore intersection test
+struct TriangleHit {
+    t: f64,
+    /// Barycentric weight of the second vertex
+    u: f64,
+    /// Barycentric weight of the third vertex
+    v: f64,
+}
+
+/// Intersects the ray with the triangle `(p0, p1, p2)` using the Möller–Trumbore algorithm, which
+/// solves for `t` and the barycentric coordinates directly, without computing the plane first.
+fn intersect(p0: Vec3, p1: Vec3, p2: Vec3, r: &Ray, ray_t: Interval) -> Option<TriangleHit> {
+    let edge1 = p1 - p0;
+    let edge2 = p2 - p0;
+
+    let pvec = r.dir.cross(&edge2);
+    let det = edge1.dot(&pvec);
+    if det.abs() < PARALLEL_EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let tvec = r.origin - p0;
+    let u = tvec.dot(&pvec) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let qvec = tvec.cross(&edge1);
+    let v = r.dir.dot(&qvec) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&qvec) * inv_det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+
+    Some(TriangleHit { t, u, v })
+}
+
+/// Texture coordinates used when a triangle doesn't have any, which makes `(u, v)` equal to the
+/// barycentric coordinates of the hit point
+const DEFAULT_TEX_COORDS: [(f64, f64); 3] = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+
+/// Per-vertex attributes of a single triangle
+struct TriangleAttributes {
+    vertices: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    tex_coords: Option<[(f64, f64); 3]>,
+}
+
+/// Builds the hit record for a triangle hit. If vertex normals are given they are interpolated
+/// with the barycentric coordinates (smooth shading), otherwise the flat geometric normal is used.
+/// Texture coordinates are interpolated the same way.
+fn hit_record(
+    attributes: TriangleAttributes,
+    mat: &Arc<dyn Material>,
+    r: &Ray,
+    hit: TriangleHit,
+) -> HitRecord {
+    let [p0, p1, p2] = attributes.vertices;
+    let geometric_normal = (p1 - p0).cross(&(p2 - p0)).unit();
+
+    let [uv0, uv1, uv2] = attributes.tex_coords.unwrap_or(DEFAULT_TEX_COORDS);
+    let w = 1.0 - hit.u - hit.v;
+    let (dpdu, dpdv) = triangle_derivatives([p0, p1, p2], [uv0, uv1, uv2], &geometric_normal);
+
+    let mut record = HitRecord {
+        t: hit.t,
+        point: r.at(hit.t),
+        normal: geometric_normal,
+        front_face: false,
+        u: w * uv0.0 + hit.u * uv1.0 + hit.v * uv2.0,
+        v: w * uv0.1 + hit.u * uv1.1 + hit.v * uv2.1,
+        dpdu,
+        dpdv,
+        mat: mat.clone(),
+    };
+
+    // The side of the surface is always decided by the real geometry, so interpolated normals
+    // can't flip which face we hit.
+    record.set_face_normal(r, &geometric_normal);
+
+    if let Some([n0, n1, n2]) = attributes.normals {
+        let shading_normal = (w * n0 + hit.u * n1 + hit.v * n2).unit();
+        record.normal = if record.front_face {
+            shading_normal
+        } else {
+            -shading_normal
+        };
+    }
+
+    record
+}
+
+/// Solves for `dp/du` and `dp/dv` from the edges of the triangle and the differences of the
+/// texture coordinates along them. Degenerate texture coordinates give an arbitrary tangent
+/// frame around the normal.
+fn triangle_derivatives(p: [Vec3; 3], uv: [(f64, f64); 3], normal: &Vec3) -> (Vec3, Vec3) {
+    let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+    let (du1, dv1) = (uv[1].0 - uv[0].0, uv[1].1 - uv[0].1);
+    let (du2, dv2) = (uv[2].0 - uv[0].0, uv[2].1 - uv[0].1);
+
+    let det = du1 * dv2 - dv1 * du2;
+    if det.abs() < 1e-12 {
+        let helper = if normal.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let dpdu = normal.cross(&helper).unit();
+        return (dpdu, normal.cross(&dpdu));
+    }
+
+    let inv_det = 1.0 / det;
+    (
+        (dv2 * e1 - dv1 * e2) * inv_det,
+        (du1 * e2 - du2 * e1) * inv_det,
+    )
+}
+
+fn triangle_bounding_box(p0: Vec3, p1: Vec3, p2: Vec3) -> Aabb {
+    Aabb::enclosing(&Aabb::from_points(p0, p1), &Aabb::from_points(p2, p2))
+}
+
+/// A single triangle with its own vertices. For models with many triangles that share vertices
+/// use `TriangleMesh` instead.
+pub struct Triangle {
+    vertices: [Vec3; 3],
+    /// Optional per-vertex normals used for smooth shading
+    normals: Option<[Vec3; 3]>,
+    /// Optional per-vertex texture coordinates
+    tex_coords: Option<[(f64, f64); 3]>,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Triangle {
+    /// Creates a flat shaded triangle. The front face is the one from which the vertices appear in
+    /// counter-clockwise order.
+    pub fn new(p0: Vec3, p1: Vec3, p2: Vec3, mat: Arc<dyn Material>) -> Self {
+        Triangle {
+            vertices: [p0, p1, p2],
+            normals: None,
+            tex_coords: None,
+            mat,
+            bbox: triangle_bounding_box(p0, p1, p2),
+        }
+    }
+
+    /// Creates a smooth shaded triangle with the normals `normals[i]` at vertex `i`
+    pub fn with_normals(vertices: [Vec3; 3], normals: [Vec3; 3], mat: Arc<dyn Material>) -> Self {
+        let [p0, p1, p2] = vertices;
+        Triangle {
+            vertices,
+            normals: Some(normals.map(|n| n.unit())),
+            tex_coords: None,
+            mat,
+            bbox: triangle_bounding_box(p0, p1, p2),
+        }
+    }
+
+    /// Sets the texture coordinates `(u, v)` at each vertex. Without them the barycentric
+    /// coordinates of the hit point are used.
+    pub fn set_tex_coords(&mut self, tex_coords: [(f64, f64); 3]) {
+        self.tex_coords = Some(tex_coords);
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> HitResult {
+        let [p0, p1, p2] = self.vertices;
+        match intersect(p0, p1, p2, r, ray_t) {
+            Some(hit) => HitResult::Hit(hit_record(
+                TriangleAttributes {
+                    vertices: self.vertices,
+                    normals: self.normals,
+                    tex_coords: self.tex_coords,
+                },
+                &self.mat,
+                r,
+                hit,
+            )),
+            None => HitResult::NoHit,
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+/// Vertex and index buffers shared by all of the faces of a mesh
+struct MeshData {
+    vertices: Vec<Vec3>,
+    /// Per-vertex normals, indexed the same way as `vertices`
+    normals: Option<Vec<Vec3>>,
+    /// Per-vertex texture coordinates, indexed the same way as `vertices`
+    tex_coords: Option<Vec<(f64, f64)>>,
+    /// Each face is described by the indices of its three vertices
+    indices: Vec<[usize; 3]>,
+    mat: Arc<dyn Material>,
+}
+
+impl MeshData {
+    fn face_vertices(&self, face: usize) -> [Vec3; 3] {
+        self.indices[face].map(|i| self.vertices[i])
+    }
+
+    fn face_attributes(&self, face: usize) -> TriangleAttributes {
+        let indices = self.indices[face];
+        TriangleAttributes {
+            vertices: self.face_vertices(face),
+            normals: self.normals.as_ref().map(|n| indices.map(|i| n[i])),
+            tex_coords: self.tex_coords.as_ref().map(|uv| indices.map(|i| uv[i])),
+        }
+    }
+}
+
+/// A single face of a mesh. It only stores its index into the shared buffers.
+struct MeshFace {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl Hittable for MeshFace {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> HitResult {
+        let [p0, p1, p2] = self.mesh.face_vertices(self.face);
+        match intersect(p0, p1, p2, r, ray_t) {
+            Some(hit) => HitResult::Hit(hit_record(
+                self.mesh.face_attributes(self.face),
+                &self.mesh.mat,
+                r,
+                hit,
+            )),
+            None => HitResult::NoHit,
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [p0, p1, p2] = self.mesh.face_vertices(self.face);
+        triangle_bounding_box(p0, p1, p2)
+    }
+}
+
+/// An indexed triangle mesh. All faces share one vertex buffer, one index buffer and one material.
+/// The faces are stored in their own BVH, so large meshes are cheap to intersect.
+pub struct TriangleMesh {
+    faces: BvhNode,
+}
+
+impl TriangleMesh {
+    /// Creates a flat shaded mesh.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of bounds of `vertices`
+    pub fn new(vertices: Vec<Vec3>, indices: Vec<[usize; 3]>, mat: Arc<dyn Material>) -> Self {
+        TriangleMesh::with_attributes(vertices, None, None, indices, mat)
+    }
+
+    /// Creates a smooth shaded mesh where `normals[i]` is the normal at `vertices[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffers have different lengths or any index is out of bounds of `vertices`
+    pub fn with_normals(
+        vertices: Vec<Vec3>,
+        normals: Vec<Vec3>,
+        indices: Vec<[usize; 3]>,
+        mat: Arc<dyn Material>,
+    ) -> Self {
+        TriangleMesh::with_attributes(vertices, Some(normals), None, indices, mat)
+    }
+
+    /// Creates a mesh with optional per-vertex normals and texture coordinates, both indexed the
+    /// same way as `vertices`. Without texture coordinates every face is mapped to the `(u, v)`
+    /// triangle `(0, 0)`, `(1, 0)`, `(0, 1)`. Normals are normalized, so they must not be zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffers have different lengths or any index is out of bounds of `vertices`
+    pub fn with_attributes(
+        vertices: Vec<Vec3>,
+        normals: Option<Vec<Vec3>>,
+        tex_coords: Option<Vec<(f64, f64)>>,
+        indices: Vec<[usize; 3]>,
+        mat: Arc<dyn Material>,
+    ) -> Self {
+        if let Some(normals) = &normals {
+            if normals.len() != vertices.len() {
+                panic!(
+                    "Mesh has {} vertices but {} normals",
+                    vertices.len(),
+                    normals.len()
+                );
+            }
+        }
+        if let Some(tex_coords) = &tex_coords {
+            if tex_coords.len() != vertices.len() {
+                panic!(
+                    "Mesh has {} vertices but {} texture coordinates",
+                    vertices.len(),
+                    tex_coords.len()
+                );
+            }
+        }
+        TriangleMesh::build(MeshData {
+            vertices,
+            normals: normals.map(|normals| normals.into_iter().map(|n| n.unit()).collect()),
+            tex_coords,
+            indices,
+            mat,
+        })
+    }
+
+    fn build(data: MeshData) -> Self {
+        if let Some(index) = data
+            .indices
+            .iter()
+            .flatten()
+            .find(|&&i| i >= data.vertices.len())
+        {
+            panic!(
+                "Mesh index {} out of bounds for {} vertices",
+                index,
+                data.vertices.len()
+            );
+        }
+
+        let mesh = Arc::new(data);
+        let mut faces = HittableList::new();
+        for face in 0..mesh.indices.len() {
+            faces.add(Box::new(MeshFace {
+                mesh: mesh.clone(),
+                face,
+            }));
+        }
+
+        TriangleMesh {
+            faces: BvhNode::new(faces),
+        }
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> HitResult {
+        self.faces.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.faces.bounding_box()
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct Interval {
     pub min: f64,
     pub max: f64,
@@ -9,6 +9,23 @@ impl Interval {
         Interval { min, max }
     }
 
+    /// An interval that contains nothing. Enclosing it with any other interval yields the other
+    /// interval.
+    pub fn empty() -> Self {
+        Interval {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// The smallest interval that contains both `a` and `b`
+    pub fn enclosing(a: Interval, b: Interval) -> Self {
+        Interval {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+
     pub fn size(&self) -> f64 {
         self.max - self.min
     }
@@ -21,6 +38,12 @@ impl Interval {
         self.min < x && x < self.max
     }
 
+    /// Pads the interval by `delta / 2` on both sides
+    pub fn expand(&self, delta: f64) -> Self {
+        let padding = delta / 2.0;
+        Interval::new(self.min - padding, self.max + padding)
+    }
+
     pub fn clamp(&self, x: f64) -> f64 {
         if x < self.min {
             self.min
@@ -1,6 +1,12 @@
+use std::sync::Arc;
+
 use rand::random;
 
-use super::{reflect, refract, HitRecord, Ray, Vec3};
+use super::{
+    reflect, refract,
+    textures::{SolidColor, Texture},
+    HitRecord, Ray, Vec3,
+};
 
 pub struct ScatterResult {
     /// How much of the incoming ray will be attenuated (absorbed)
@@ -9,19 +15,28 @@ pub struct ScatterResult {
     pub scattered: Ray,
 }
 
-pub trait Material {
+pub trait Material: Send + Sync {
     /// Some means that the ray scattered, `None` means that the ray was absorbed.
     fn scatter(&self, r: &Ray, rec: &HitRecord) -> Option<ScatterResult>;
+
+    /// The light emitted by the material at the hit point. Most materials don't emit any light.
+    fn emitted(&self, _r: &Ray, _rec: &HitRecord) -> Vec3 {
+        Vec3::zero()
+    }
 }
 
 /// A material implementing lambertian reflectance. In this implementation
 /// we always assume that the ray always scatters (no probabilistic scattering).
 pub struct Lambertian {
-    albedo: Vec3,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Vec3) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
@@ -36,19 +51,23 @@ impl Material for Lambertian {
         }
         Some(ScatterResult {
             scattered: Ray::new(rec.point, scatter_dir),
-            attenuation: self.albedo,
+            attenuation: self.albedo.value(rec.u, rec.v, &rec.point),
         })
     }
 }
 
 /// A reflective material
 pub struct Metal {
-    albedo: Vec3,
+    albedo: Arc<dyn Texture>,
     fuzziness: f64,
 }
 
 impl Metal {
     pub fn new(albedo: Vec3, fuzziness: f64) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)), fuzziness)
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>, fuzziness: f64) -> Self {
         Self { albedo, fuzziness }
     }
 }
@@ -63,7 +82,7 @@ impl Material for Metal {
         }
         Some(ScatterResult {
             scattered: Ray::new(rec.point, reflected),
-            attenuation: self.albedo,
+            attenuation: self.albedo.value(rec.u, rec.v, &rec.point),
         })
     }
 }
@@ -94,7 +113,7 @@ impl Material for Dielectric {
         };
 
         let unit_dir = r.dir.unit();
-        let cos_theta = (-1.0 * unit_dir.dot(&rec.normal)).min(1.0);
+        let cos_theta = (-unit_dir.dot(&rec.normal)).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         // Sometimes the Snell's law equation cannot be solved and therefore we have perfect
@@ -113,3 +132,30 @@ impl Material for Dielectric {
         })
     }
 }
+
+/// A material that emits light uniformly in all directions and doesn't reflect any
+pub struct DiffuseLight {
+    radiance: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    /// The radiance can be brighter than 1.0 in any channel, which is usually needed for small
+    /// lights to illuminate the scene.
+    pub fn new(radiance: Vec3) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(radiance)))
+    }
+
+    pub fn from_texture(radiance: Arc<dyn Texture>) -> Self {
+        Self { radiance }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _r: &Ray, _rec: &HitRecord) -> Option<ScatterResult> {
+        None
+    }
+
+    fn emitted(&self, _r: &Ray, rec: &HitRecord) -> Vec3 {
+        self.radiance.value(rec.u, rec.v, &rec.point)
+    }
+}
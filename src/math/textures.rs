@@ -0,0 +1,220 @@
+pub mod noise;
+pub mod procedural;
+
+use std::{path::Path, sync::Arc};
+
+use super::{color::ColorSpace, Vec3};
+use crate::{
+    loaders::{self, LoadError},
+    renderers::Image,
+};
+
+pub use procedural::{Marble, NoiseTexture, Turbulence, Voronoi, Wood};
+
+/// A color that varies over the surface of an object
+pub trait Texture: Send + Sync {
+    /// The color at the surface coordinates `(u, v)` and the point `p` in space
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3;
+}
+
+/// A texture with the same color everywhere
+pub struct SolidColor {
+    albedo: Vec3,
+}
+
+impl SolidColor {
+    pub fn new(albedo: Vec3) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Vec3) -> Vec3 {
+        self.albedo
+    }
+}
+
+/// A checker pattern of cubes in space. The pattern doesn't depend on how the surface is
+/// parameterized, so it looks the same on every object.
+pub struct Checker {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    /// `scale` is the edge length of a single cube of the pattern
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            inv_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, even: Vec3, odd: Vec3) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        let x = (self.inv_scale * p.x).floor() as i64;
+        let y = (self.inv_scale * p.y).floor() as i64;
+        let z = (self.inv_scale * p.z).floor() as i64;
+
+        if (x + y + z).rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// A checker pattern over the surface coordinates with the given number of squares along `u`
+/// and `v`
+pub struct UvChecker {
+    u_squares: f64,
+    v_squares: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvChecker {
+    pub fn new(
+        u_squares: f64,
+        v_squares: f64,
+        even: Arc<dyn Texture>,
+        odd: Arc<dyn Texture>,
+    ) -> Self {
+        Self {
+            u_squares,
+            v_squares,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(u_squares: f64, v_squares: f64, even: Vec3, odd: Vec3) -> Self {
+        Self::new(
+            u_squares,
+            v_squares,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, p: &Vec3) -> Vec3 {
+        let x = (u * self.u_squares).floor() as i64;
+        let y = (v * self.v_squares).floor() as i64;
+
+        if (x + y).rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// How an image texture is sampled between the pixel centers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Takes the color of the closest pixel
+    Nearest,
+    /// Interpolates between the four closest pixels
+    Bilinear,
+}
+
+/// What happens to texture coordinates outside of `[0, 1]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    /// The image is tiled
+    Repeat,
+    /// The edge pixels are extended
+    Clamp,
+}
+
+/// A texture mapped onto the surface coordinates from an image. `(0, 0)` is the lower-left
+/// corner of the image and `(1, 1)` the upper-right one.
+pub struct ImageTexture {
+    /// Pixels in linear space
+    image: Image,
+    filter: Filter,
+    wrap_mode: WrapMode,
+}
+
+impl ImageTexture {
+    /// Creates a bilinearly filtered, repeating texture from a linear image
+    pub fn new(image: Image) -> Self {
+        Self {
+            image,
+            filter: Filter::Bilinear,
+            wrap_mode: WrapMode::Repeat,
+        }
+    }
+
+    /// Loads the texture from a PNG, PPM, Radiance HDR or PFM file. The 8-bit formats are
+    /// decoded from sRGB.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        Self::load_as(path, ColorSpace::Srgb)
+    }
+
+    /// Loads the texture like `load` and converts it from sRGB primaries into the linear working
+    /// space the scene is rendered in
+    pub fn load_as(path: impl AsRef<Path>, working_space: ColorSpace) -> Result<Self, LoadError> {
+        let mut image = loaders::load_image(path)?;
+        image.convert_color_space(ColorSpace::Srgb, working_space);
+        Ok(Self::new(image))
+    }
+
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+
+    pub fn set_wrap_mode(&mut self, wrap_mode: WrapMode) {
+        self.wrap_mode = wrap_mode;
+    }
+
+    /// Returns the pixel at integer coordinates that may lie outside of the image
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        let (width, height) = (self.image.width as i64, self.image.height as i64);
+        let (x, y) = match self.wrap_mode {
+            WrapMode::Repeat => (x.rem_euclid(width), y.rem_euclid(height)),
+            WrapMode::Clamp => (x.clamp(0, width - 1), y.clamp(0, height - 1)),
+        };
+        *self.image.get_pixel(x as usize, y as usize)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Vec3) -> Vec3 {
+        if self.image.pixels().is_empty() {
+            return Vec3::zero();
+        }
+
+        // Continuous pixel coordinates, where the rows are flipped because `v` points up
+        let x = u * self.image.width as f64;
+        let y = (1.0 - v) * self.image.height as f64;
+
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // Shift so that the pixel centers lie on integer coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                let top = (1.0 - tx) * self.texel(x0, y0) + tx * self.texel(x0 + 1, y0);
+                let bottom = (1.0 - tx) * self.texel(x0, y0 + 1) + tx * self.texel(x0 + 1, y0 + 1);
+                (1.0 - ty) * top + ty * bottom
+            }
+        }
+    }
+}
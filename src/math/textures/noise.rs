@@ -0,0 +1,195 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::math::Vec3;
+
+/// Number of gradients and the period of the noise along each axis
+const POINT_COUNT: usize = 256;
+
+/// How the noise is blended between the lattice points
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Plain trilinear interpolation, which leaves visible creases along the lattice
+    Trilinear,
+    /// Trilinear interpolation with the weights smoothed by the Hermite cubic `3t^2 - 2t^3`
+    Hermite,
+}
+
+/// Gradient noise as described by Ken Perlin. Every lattice point gets a random unit gradient and
+/// the noise at a point is the blend of the gradients' dot products with the offsets to it.
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+    interpolation: Interpolation,
+}
+
+impl Perlin {
+    /// Creates the noise generator. The same seed always produces the same noise.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| random_unit_vector(&mut rng))
+            .collect();
+
+        Self {
+            gradients,
+            perm_x: permutation(&mut rng),
+            perm_y: permutation(&mut rng),
+            perm_z: permutation(&mut rng),
+            interpolation: Interpolation::Hermite,
+        }
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// Noise value at `p`, roughly in `[-1, 1]`
+    pub fn noise(&self, p: &Vec3) -> f64 {
+        let (fx, fy, fz) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (u, v, w) = (p.x - fx, p.y - fy, p.z - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+
+        let (wu, wv, ww) = match self.interpolation {
+            Interpolation::Trilinear => (u, v, w),
+            Interpolation::Hermite => (hermite(u), hermite(v), hermite(w)),
+        };
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let gradient = self.gradients[self.hash(i + di, j + dj, k + dk)];
+                    let offset = Vec3::new(u - di as f64, v - dj as f64, w - dk as f64);
+                    let weight = lerp_weight(wu, di) * lerp_weight(wv, dj) * lerp_weight(ww, dk);
+                    accum += weight * gradient.dot(&offset);
+                }
+            }
+        }
+        accum
+    }
+
+    /// Sum of `octaves` layers of noise, each with double the frequency and half the amplitude of
+    /// the previous one. The result is non-negative.
+    pub fn turbulence(&self, p: &Vec3, octaves: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut point = *p;
+        let mut weight = 1.0;
+
+        for _ in 0..octaves {
+            accum += weight * self.noise(&point);
+            weight *= 0.5;
+            point = point * 2.0;
+        }
+
+        accum.abs()
+    }
+
+    /// Hashes the lattice point into an index of the gradient table
+    fn hash(&self, i: i64, j: i64, k: i64) -> usize {
+        let mask = POINT_COUNT as i64 - 1;
+        self.perm_x[(i & mask) as usize]
+            ^ self.perm_y[(j & mask) as usize]
+            ^ self.perm_z[(k & mask) as usize]
+    }
+}
+
+/// Cellular noise by Steven Worley. Space is split into unit cells that each contain one random
+/// feature point, and the noise is the distance to the closest feature points.
+pub struct Worley {
+    /// Offset of the feature point inside of the cell, for every hashed cell
+    points: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Worley {
+    /// Creates the noise generator. The same seed always produces the same cells.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let points = (0..POINT_COUNT)
+            .map(|_| Vec3::new(rng.random(), rng.random(), rng.random()))
+            .collect();
+
+        Self {
+            points,
+            perm_x: permutation(&mut rng),
+            perm_y: permutation(&mut rng),
+            perm_z: permutation(&mut rng),
+        }
+    }
+
+    /// Returns the distances `(F1, F2)` to the closest and the second closest feature point, and
+    /// the hash of the cell that contains the closest one. The hash can be used to give every
+    /// cell its own color.
+    pub fn distances(&self, p: &Vec3) -> (f64, f64, usize) {
+        let (i, j, k) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+        let mask = POINT_COUNT as i64 - 1;
+
+        let mut f1 = f64::INFINITY;
+        let mut f2 = f64::INFINITY;
+        let mut closest_cell = 0;
+
+        // The closest points are always in the cell of `p` or one of its neighbours
+        for ci in i - 1..=i + 1 {
+            for cj in j - 1..=j + 1 {
+                for ck in k - 1..=k + 1 {
+                    let cell = self.perm_x[(ci & mask) as usize]
+                        ^ self.perm_y[(cj & mask) as usize]
+                        ^ self.perm_z[(ck & mask) as usize];
+                    let feature = Vec3::new(ci as f64, cj as f64, ck as f64) + self.points[cell];
+                    let distance = (feature - *p).length();
+
+                    if distance < f1 {
+                        f2 = f1;
+                        f1 = distance;
+                        closest_cell = cell;
+                    } else if distance < f2 {
+                        f2 = distance;
+                    }
+                }
+            }
+        }
+
+        (f1, f2, closest_cell)
+    }
+}
+
+/// Weight of the lattice point `d` (0 or 1) when interpolating with the factor `t`
+fn lerp_weight(t: f64, d: i64) -> f64 {
+    if d == 1 {
+        t
+    } else {
+        1.0 - t
+    }
+}
+
+fn hermite(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn random_unit_vector(rng: &mut StdRng) -> Vec3 {
+    loop {
+        let p = Vec3::new(
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+        );
+        let l = p.length_squared();
+        if 1e-16 < l && l <= 1.0 {
+            return p / l.sqrt();
+        }
+    }
+}
+
+/// A random permutation of `0..POINT_COUNT` (Fisher-Yates shuffle)
+fn permutation(rng: &mut StdRng) -> Vec<usize> {
+    let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+    for i in (1..POINT_COUNT).rev() {
+        let target = rng.random_range(0..=i);
+        perm.swap(i, target);
+    }
+    perm
+}
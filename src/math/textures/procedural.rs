@@ -0,0 +1,156 @@
+use super::{
+    noise::{Perlin, Worley},
+    Texture,
+};
+use crate::math::Vec3;
+
+/// Number of noise octaves used by textures that are perturbed with turbulence
+const DEFAULT_OCTAVES: usize = 7;
+
+/// Plain Perlin noise mapped to `[0, 1]` and multiplied by a color
+pub struct NoiseTexture {
+    perlin: Perlin,
+    /// Frequency of the noise: higher values give smaller features
+    scale: f64,
+    color: Vec3,
+}
+
+impl NoiseTexture {
+    pub fn new(seed: u64, scale: f64, color: Vec3) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            scale,
+            color,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        0.5 * (1.0 + self.perlin.noise(&(self.scale * *p))) * self.color
+    }
+}
+
+/// Turbulence (summed octaves of noise), which looks like smoke or clouds
+pub struct Turbulence {
+    perlin: Perlin,
+    scale: f64,
+    octaves: usize,
+    color: Vec3,
+}
+
+impl Turbulence {
+    pub fn new(seed: u64, scale: f64, octaves: usize, color: Vec3) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            scale,
+            octaves,
+            color,
+        }
+    }
+}
+
+impl Texture for Turbulence {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let t = self.perlin.turbulence(&(self.scale * *p), self.octaves);
+        t.min(1.0) * self.color
+    }
+}
+
+/// Marble-like stripes along the `z` axis whose phase is distorted by turbulence
+pub struct Marble {
+    perlin: Perlin,
+    /// Frequency of the stripes
+    frequency: f64,
+    /// How strongly the turbulence bends the stripes
+    turbulence: f64,
+    color: Vec3,
+}
+
+impl Marble {
+    pub fn new(seed: u64, frequency: f64, turbulence: f64, color: Vec3) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            frequency,
+            turbulence,
+            color,
+        }
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let phase =
+            self.frequency * p.z + self.turbulence * self.perlin.turbulence(p, DEFAULT_OCTAVES);
+        0.5 * (1.0 + phase.sin()) * self.color
+    }
+}
+
+/// Concentric growth rings around the `y` axis, perturbed by noise
+pub struct Wood {
+    perlin: Perlin,
+    /// Number of rings per unit of distance from the axis
+    frequency: f64,
+    /// How strongly the noise distorts the rings
+    turbulence: f64,
+    light: Vec3,
+    dark: Vec3,
+}
+
+impl Wood {
+    pub fn new(seed: u64, frequency: f64, turbulence: f64, light: Vec3, dark: Vec3) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            frequency,
+            turbulence,
+            light,
+            dark,
+        }
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let radius = (p.x * p.x + p.z * p.z).sqrt();
+        let rings = self.frequency * radius + self.turbulence * self.perlin.noise(p);
+        // Sharpen the ring profile so that the dark late wood is thinner than the light early wood
+        let t = rings.rem_euclid(1.0).powi(3);
+        (1.0 - t) * self.light + t * self.dark
+    }
+}
+
+/// Worley (Voronoi) cells, where every cell is colored by the distance to its feature point and
+/// the borders between cells are drawn with the edge color
+pub struct Voronoi {
+    worley: Worley,
+    /// Frequency of the cells: higher values give smaller cells
+    scale: f64,
+    cell: Vec3,
+    edge: Vec3,
+}
+
+/// Width of the border between two cells, in units of the cell size
+const EDGE_WIDTH: f64 = 0.05;
+
+impl Voronoi {
+    pub fn new(seed: u64, scale: f64, cell: Vec3, edge: Vec3) -> Self {
+        Self {
+            worley: Worley::new(seed),
+            scale,
+            cell,
+            edge,
+        }
+    }
+}
+
+impl Texture for Voronoi {
+    fn value(&self, _u: f64, _v: f64, p: &Vec3) -> Vec3 {
+        let (f1, f2, _) = self.worley.distances(&(self.scale * *p));
+        // Points close to the border are almost as close to the second feature point as the first
+        if f2 - f1 < EDGE_WIDTH {
+            return self.edge;
+        }
+        let shade = 1.0 - 0.5 * f1.min(1.0);
+        shade * self.cell
+    }
+}
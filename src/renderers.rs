@@ -1,20 +1,90 @@
+/// Implements `new` and `from_writer` on top of the renderer's `with_output`, which sets every
+/// other option to its default. `$description` completes "Writes ..." in their docs.
+macro_rules! output_constructors {
+    ($renderer:ident, $description:literal) => {
+        impl $renderer {
+            #[doc = concat!("Writes ", $description, " to the file at `path`")]
+            pub fn new(path: impl AsRef<std::path::Path>) -> Self {
+                Self::with_output($crate::renderers::Output::File(path.as_ref().to_path_buf()))
+            }
+
+            #[doc = concat!(
+                                "Writes ",
+                                $description,
+                                " to any writer, such as standard output or an in-memory buffer"
+                            )]
+            pub fn from_writer(writer: impl std::io::Write + 'static) -> Self {
+                Self::with_output($crate::renderers::Output::Writer(Box::new(writer)))
+            }
+        }
+    };
+}
+
+pub mod exr;
+pub mod hdr;
+pub mod multi;
+pub mod pfm;
+pub mod png;
 pub mod ppm;
+pub mod tonemap;
+
+pub use exr::{Compression, PixelType, EXR};
+pub use hdr::HDR;
+pub use multi::MultiRenderer;
+pub use pfm::PFM;
+pub use png::{BitDepth, PNG};
+pub use ppm::{PpmFormat, PPM};
+pub use tonemap::{ToneMapper, ToneMapping};
 
-pub use ppm::PPM;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
 
+use crate::math::{color::ColorSpace, Vec3};
+
+/// Encodes a finished image, e.g. into a file. Renderers can be reused for several images.
 pub trait Renderer {
-    fn draw(self, image: &Image);
+    fn draw(&mut self, image: &Image) -> io::Result<()>;
+}
+
+impl<R: Renderer + ?Sized> Renderer for Box<R> {
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        (**self).draw(image)
+    }
 }
 
+/// Where a renderer writes the encoded image
+pub(crate) enum Output {
+    File(PathBuf),
+    Writer(Box<dyn Write>),
+}
+
+impl Output {
+    /// Creates (or truncates) the file and returns a buffered writer to it
+    pub(crate) fn open(&mut self) -> io::Result<Box<dyn Write + '_>> {
+        Ok(match self {
+            Output::File(path) => Box::new(BufWriter::new(File::create(path)?)),
+            Output::Writer(writer) => Box::new(writer),
+        })
+    }
+}
+
+/// An 8-bit display color, as written by the LDR renderers
 #[derive(Clone, Copy)]
 pub struct Color {
-    r: u8,
-    g: u8,
-    b: u8,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
 }
 
+/// A floating point frame buffer holding linear RGB radiance. Values are not limited to
+/// `[0, 1]`; they are only quantized when a renderer writes the image out.
+#[derive(Clone)]
 pub struct Image {
-    pixels: Vec<Vec<Color>>,
+    /// Row-major pixels, starting in the upper-left corner
+    pixels: Vec<Vec3>,
     pub width: usize,
     pub height: usize,
 }
@@ -35,32 +105,82 @@ impl Image {
         Image {
             width,
             height,
-            pixels: vec![vec![Color::new(0, 0, 0); width]; height],
+            pixels: vec![Vec3::zero(); width * height],
         }
     }
 
-    pub fn set_pixel(&mut self, x: usize, y: usize, c: Color) {
-        self.pixels[y][x] = c;
+    /// Creates an image from row-major pixels. Panics if there are not `width * height` pixels.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Vec3>) -> Self {
+        if pixels.len() != width * height {
+            panic!(
+                "Image of size ({}, {}) needs {} pixels, got {}",
+                width,
+                height,
+                width * height,
+                pixels.len()
+            );
+        }
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, c: Vec3) {
+        self.pixels[y * self.width + x] = c;
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> &Vec3 {
+        &self.pixels[y * self.width + x]
     }
 
-    pub fn get_pixel(&self, x: usize, y: usize) -> &Color {
-        &self.pixels[y][x]
+    /// Adds `c` to the pixel, for accumulating samples over several passes
+    pub fn accumulate(&mut self, x: usize, y: usize, c: Vec3) {
+        let idx = y * self.width + x;
+        self.pixels[idx] = self.pixels[idx] + c;
+    }
+
+    /// All pixels in row-major order
+    pub fn pixels(&self) -> &[Vec3] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Vec3] {
+        &mut self.pixels
+    }
+
+    /// Converts every pixel from the linear color space `from` into `to`
+    pub fn convert_color_space(&mut self, from: ColorSpace, to: ColorSpace) {
+        if from == to {
+            return;
+        }
+        let matrix = from.conversion_to(to);
+        for pixel in self.pixels.iter_mut() {
+            let [r, g, b] =
+                matrix.map(|row| row[0] * pixel.x + row[1] * pixel.y + row[2] * pixel.z);
+            *pixel = Vec3::new(r, g, b);
+        }
     }
 
     /// Fills rectangle with the upper-left corner at `(x,y)` and with the provided width
     /// and height
-    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Vec3) {
         if y + height >= self.height || x + width >= self.width {
             panic!(
                 "Rectangle at ({}, {}) with size ({}, {}) out of bounds",
                 x, y, width, height
             );
         }
-        self.pixels.iter_mut().skip(y).take(height).for_each(|row| {
-            row.iter_mut()
-                .skip(x)
-                .take(width)
-                .for_each(|pixel| *pixel = color);
-        });
+        self.pixels
+            .chunks_exact_mut(self.width)
+            .skip(y)
+            .take(height)
+            .for_each(|row| {
+                row.iter_mut()
+                    .skip(x)
+                    .take(width)
+                    .for_each(|pixel| *pixel = color);
+            });
     }
 }
@@ -0,0 +1,365 @@
+use std::io::{self, Write};
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use super::{Image, Output};
+use crate::math::Vec3;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+/// Version 2 of the file format, single-part scanline image
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+const COMPRESSION_LEVEL: u8 = 6;
+/// Longest channel name readers accept without the long names flag in the version field
+const MAX_NAME_LENGTH: usize = 31;
+
+/// How the samples of every channel are stored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PixelType {
+    /// 16-bit floats, plenty for color and half the size
+    #[default]
+    Half,
+    /// 32-bit floats, e.g. for depth
+    Float,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// Run length encoding, fast and good for flat areas
+    Rle,
+    /// Zlib compression of single scanlines
+    Zips,
+    /// Zlib compression of blocks of 16 scanlines
+    #[default]
+    Zip,
+}
+
+impl Compression {
+    fn lines_per_block(&self) -> usize {
+        match self {
+            Compression::Zip => 16,
+            _ => 1,
+        }
+    }
+}
+
+/// Writes the linear radiance without clamping or tone mapping as an OpenEXR scanline image.
+/// Extra named channels, such as depth or normals, are stored in the same file.
+pub struct EXR {
+    output: Output,
+    pixel_type: PixelType,
+    compression: Compression,
+    alpha: bool,
+    /// Extra channels with one value per pixel in row-major order
+    channels: Vec<(String, Vec<f64>)>,
+}
+
+output_constructors!(EXR, "a ZIP compressed half float RGB image");
+
+impl EXR {
+    fn with_output(output: Output) -> Self {
+        EXR {
+            output,
+            pixel_type: PixelType::default(),
+            compression: Compression::default(),
+            alpha: false,
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn set_pixel_type(&mut self, pixel_type: PixelType) {
+        self.pixel_type = pixel_type;
+    }
+
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Adds an alpha channel. Every pixel of a render is covered, so it is fully opaque.
+    pub fn set_alpha(&mut self, alpha: bool) {
+        self.alpha = alpha;
+    }
+
+    /// Adds a channel with one value per pixel in row-major order, e.g. `"Z"` for depth. The
+    /// name has to be 1 to 31 bytes long without NUL characters, or drawing fails.
+    pub fn add_channel(&mut self, name: &str, values: Vec<f64>) {
+        self.channels.push((name.to_string(), values));
+    }
+
+    /// Adds the three channels `<layer>.X`, `<layer>.Y` and `<layer>.Z`, e.g. `"N"` for normals
+    pub fn add_vector_channels(&mut self, layer: &str, values: &[Vec3]) {
+        for (axis, component) in ["X", "Y", "Z"].iter().enumerate() {
+            let name = format!("{}.{}", layer, component);
+            self.add_channel(&name, values.iter().map(|v| v[axis]).collect());
+        }
+    }
+
+    fn bytes_per_sample(&self) -> usize {
+        match self.pixel_type {
+            PixelType::Half => 2,
+            PixelType::Float => 4,
+        }
+    }
+
+    fn write_sample(&self, value: f64, out: &mut Vec<u8>) {
+        match self.pixel_type {
+            PixelType::Half => out.extend_from_slice(&f32_to_f16(value as f32).to_le_bytes()),
+            PixelType::Float => out.extend_from_slice(&(value as f32).to_le_bytes()),
+        }
+    }
+
+    fn header(&self, channels: &[(String, Vec<f64>)], width: usize, height: usize) -> Vec<u8> {
+        let mut header = Vec::new();
+
+        let mut chlist = Vec::new();
+        for (name, _) in channels {
+            chlist.extend_from_slice(name.as_bytes());
+            chlist.push(0);
+            let pixel_type: i32 = match self.pixel_type {
+                PixelType::Half => 1,
+                PixelType::Float => 2,
+            };
+            chlist.extend_from_slice(&pixel_type.to_le_bytes());
+            // Not perceptually linear, three reserved bytes and no subsampling
+            chlist.extend_from_slice(&[0, 0, 0, 0]);
+            chlist.extend_from_slice(&1i32.to_le_bytes());
+            chlist.extend_from_slice(&1i32.to_le_bytes());
+        }
+        chlist.push(0);
+        write_attribute(&mut header, "channels", "chlist", &chlist);
+
+        let compression = match self.compression {
+            Compression::None => 0,
+            Compression::Rle => 1,
+            Compression::Zips => 2,
+            Compression::Zip => 3,
+        };
+        write_attribute(&mut header, "compression", "compression", &[compression]);
+
+        let window: Vec<u8> = [0, 0, width as i32 - 1, height as i32 - 1]
+            .iter()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        write_attribute(&mut header, "dataWindow", "box2i", &window);
+        write_attribute(&mut header, "displayWindow", "box2i", &window);
+        // Scanlines are stored top to bottom
+        write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        write_attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        write_attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        header.push(0);
+        header
+    }
+
+    /// Compresses one block of scanlines. Blocks that don't get smaller are stored as they are,
+    /// which is how readers tell the two apart.
+    fn compress(&self, raw: Vec<u8>) -> Vec<u8> {
+        let compressed = match self.compression {
+            Compression::None => return raw,
+            Compression::Rle => rle_compress(&predict(&interleave(&raw))),
+            Compression::Zips | Compression::Zip => {
+                compress_to_vec_zlib(&predict(&interleave(&raw)), COMPRESSION_LEVEL)
+            }
+        };
+        if compressed.len() < raw.len() {
+            compressed
+        } else {
+            raw
+        }
+    }
+}
+
+impl super::Renderer for EXR {
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let pixel_count = image.width * image.height;
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+        // The data window of an empty image would end before it starts
+        if pixel_count == 0 {
+            return Err(invalid(format!(
+                "cannot write an empty {}x{} image",
+                image.width, image.height
+            )));
+        }
+
+        let component = |axis: usize| image.pixels().iter().map(|p| p[axis]).collect();
+        let mut channels: Vec<(String, Vec<f64>)> = vec![
+            ("R".to_string(), component(0)),
+            ("G".to_string(), component(1)),
+            ("B".to_string(), component(2)),
+        ];
+        if self.alpha {
+            channels.push(("A".to_string(), vec![1.0; pixel_count]));
+        }
+        for (name, values) in &self.channels {
+            // Names are stored null-terminated
+            if name.is_empty() || name.len() > MAX_NAME_LENGTH || name.contains('\0') {
+                return Err(invalid(format!("invalid channel name {:?}", name)));
+            }
+            if values.len() != pixel_count {
+                return Err(invalid(format!(
+                    "channel {} has {} values, expected {}",
+                    name,
+                    values.len(),
+                    pixel_count
+                )));
+            }
+            channels.push((name.clone(), values.clone()));
+        }
+        // Readers expect the channels sorted by name
+        channels.sort_by(|a, b| a.0.cmp(&b.0));
+        if let Some(pair) = channels.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(invalid(format!("duplicate channel {}", pair[0].0)));
+        }
+
+        let lines_per_block = self.compression.lines_per_block();
+        let line_bytes = image.width * channels.len() * self.bytes_per_sample();
+        let mut blocks = Vec::new();
+        for first_line in (0..image.height).step_by(lines_per_block) {
+            let last_line = (first_line + lines_per_block).min(image.height);
+            let mut raw = Vec::with_capacity((last_line - first_line) * line_bytes);
+            // Within a scanline every channel stores all of its samples before the next one
+            for y in first_line..last_line {
+                for (_, values) in &channels {
+                    for value in &values[y * image.width..(y + 1) * image.width] {
+                        self.write_sample(*value, &mut raw);
+                    }
+                }
+            }
+            blocks.push((first_line, self.compress(raw)));
+        }
+
+        let header = self.header(&channels, image.width, image.height);
+
+        let mut out = self.output.open()?;
+        out.write_all(&MAGIC)?;
+        out.write_all(&VERSION)?;
+        out.write_all(&header)?;
+
+        // The offset table points at the start of every block from the start of the file
+        let mut offset = (MAGIC.len() + VERSION.len() + header.len() + 8 * blocks.len()) as u64;
+        for (_, data) in &blocks {
+            out.write_all(&offset.to_le_bytes())?;
+            offset += 8 + data.len() as u64;
+        }
+        for (first_line, data) in &blocks {
+            out.write_all(&(*first_line as i32).to_le_bytes())?;
+            out.write_all(&(data.len() as i32).to_le_bytes())?;
+            out.write_all(data)?;
+        }
+        out.flush()
+    }
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+/// Moves the bytes at even indices to the first half and the ones at odd indices to the second,
+/// so that the similar high bytes of neighbouring samples end up next to each other
+fn interleave(raw: &[u8]) -> Vec<u8> {
+    raw.iter()
+        .step_by(2)
+        .chain(raw.iter().skip(1).step_by(2))
+        .copied()
+        .collect()
+}
+
+/// Replaces every byte with its difference to the previous one
+fn predict(data: &[u8]) -> Vec<u8> {
+    let mut out = data.to_vec();
+    for i in 1..data.len() {
+        out[i] = data[i].wrapping_sub(data[i - 1]).wrapping_add(128);
+    }
+    out
+}
+
+/// A positive count `n` repeats the next byte `n + 1` times, a negative one is followed by `-n`
+/// literal bytes
+fn rle_compress(data: &[u8]) -> Vec<u8> {
+    const MIN_RUN: usize = 3;
+    const MAX_RUN: usize = 128;
+    const MAX_LITERALS: usize = 127;
+
+    let run_length = |start: usize| {
+        data[start..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&b| b == data[start])
+            .count()
+    };
+
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let run = run_length(pos);
+        if run >= MIN_RUN {
+            out.push((run - 1) as u8);
+            out.push(data[pos]);
+            pos += run;
+            continue;
+        }
+
+        let start = pos;
+        while pos < data.len() && pos - start < MAX_LITERALS && run_length(pos) < MIN_RUN {
+            pos += 1;
+        }
+        out.push((-((pos - start) as i8)) as u8);
+        out.extend_from_slice(&data[start..pos]);
+    }
+    out
+}
+
+/// Converts to an IEEE 754 half precision float, rounding to the nearest even value
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        // Infinity or NaN, which must keep a mantissa bit set
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    let round = |value: u32, shift: u32| {
+        let truncated = value >> shift;
+        let remainder = value & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        if remainder > halfway || (remainder == halfway && truncated & 1 == 1) {
+            truncated + 1
+        } else {
+            truncated
+        }
+    };
+
+    if half_exponent <= 0 {
+        // Too small for a normal half, so it becomes subnormal or zero
+        if half_exponent < -10 {
+            return sign;
+        }
+        return sign | round(mantissa | 0x80_0000, (14 - half_exponent) as u32) as u16;
+    }
+
+    // Rounding up may carry into the exponent, which is still the correct result
+    sign | round(((half_exponent as u32) << 23) | mantissa, 13) as u16
+}
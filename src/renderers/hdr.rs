@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+
+use super::{Image, Output};
+use crate::math::Vec3;
+
+/// Longest run or literal sequence a single count byte of the RLE scanlines can describe
+const MAX_RUN: usize = 127;
+const MAX_LITERALS: usize = 128;
+/// Runs shorter than this are cheaper to store as literals
+const MIN_RUN: usize = 3;
+
+/// Writes the linear radiance as a Radiance `.hdr` (RGBE) image
+pub struct HDR {
+    output: Output,
+    run_length_encoding: bool,
+}
+
+output_constructors!(HDR, "a run-length encoded image");
+
+impl HDR {
+    fn with_output(output: Output) -> Self {
+        HDR {
+            output,
+            run_length_encoding: true,
+        }
+    }
+
+    /// Enables or disables the new-style run-length encoding of scanlines. Defaults to enabled.
+    pub fn set_run_length_encoding(&mut self, enabled: bool) {
+        self.run_length_encoding = enabled;
+    }
+}
+
+impl super::Renderer for HDR {
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let mut out = self.output.open()?;
+        write!(
+            out,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            image.height, image.width
+        )?;
+
+        // Readers only recognize RLE scanlines of this width, the rest are stored flat
+        let encode = self.run_length_encoding && (8..0x8000).contains(&image.width);
+        let mut line = Vec::new();
+        for row in image.pixels().chunks_exact(image.width.max(1)) {
+            let rgbe: Vec<[u8; 4]> = row.iter().map(|&c| vec3_to_rgbe(c)).collect();
+            line.clear();
+            if encode {
+                line.extend_from_slice(&[2, 2, (image.width >> 8) as u8, image.width as u8]);
+                // Every component is encoded separately
+                for component in 0..4 {
+                    let bytes: Vec<u8> = rgbe.iter().map(|p| p[component]).collect();
+                    rle_encode(&bytes, &mut line);
+                }
+            } else {
+                line.extend(rgbe.iter().flatten());
+            }
+            out.write_all(&line)?;
+        }
+        out.flush()
+    }
+}
+
+/// Stores the color as three 8-bit mantissas sharing the exponent of the brightest channel
+fn vec3_to_rgbe(c: Vec3) -> [u8; 4] {
+    let (r, g, b) = (c.x.max(0.0), c.y.max(0.0), c.z.max(0.0));
+    let brightest = r.max(g).max(b);
+    if brightest < 1e-32 {
+        return [0; 4];
+    }
+
+    // `brightest = mantissa * 2^exponent` with the mantissa in `[0.5, 1)`
+    let mut exponent = brightest.log2().floor() as i32 + 1;
+    if brightest / 2f64.powi(exponent) >= 1.0 {
+        exponent += 1;
+    }
+    if exponent > 127 {
+        return [255, 255, 255, 255];
+    }
+
+    let scale = 256.0 / 2f64.powi(exponent);
+    [
+        (r * scale) as u8,
+        (g * scale) as u8,
+        (b * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+/// Count bytes above 128 repeat the next byte `count - 128` times, the others are followed by
+/// `count` literal bytes
+fn rle_encode(data: &[u8], out: &mut Vec<u8>) {
+    let run_length = |start: usize| {
+        data[start..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&b| b == data[start])
+            .count()
+    };
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let run = run_length(pos);
+        if run >= MIN_RUN {
+            out.push(128 + run as u8);
+            out.push(data[pos]);
+            pos += run;
+            continue;
+        }
+
+        let start = pos;
+        while pos < data.len() && pos - start < MAX_LITERALS && run_length(pos) < MIN_RUN {
+            pos += 1;
+        }
+        out.push((pos - start) as u8);
+        out.extend_from_slice(&data[start..pos]);
+    }
+}
@@ -0,0 +1,41 @@
+use std::io;
+
+use super::{Image, Renderer};
+
+/// Hands the same image to several renderers, so one render can be written in multiple formats
+#[derive(Default)]
+pub struct MultiRenderer {
+    renderers: Vec<Box<dyn Renderer>>,
+}
+
+impl MultiRenderer {
+    pub fn new() -> Self {
+        MultiRenderer {
+            renderers: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, renderer: impl Renderer + 'static) {
+        self.renderers.push(Box::new(renderer));
+    }
+
+    /// Adds the renderer and returns `self`, for chaining
+    pub fn with(mut self, renderer: impl Renderer + 'static) -> Self {
+        self.add(renderer);
+        self
+    }
+}
+
+impl Renderer for MultiRenderer {
+    /// Draws with every renderer, even if an earlier one fails, and returns the first error
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let mut result = Ok(());
+        for renderer in self.renderers.iter_mut() {
+            let drawn = renderer.draw(image);
+            if result.is_ok() {
+                result = drawn;
+            }
+        }
+        result
+    }
+}
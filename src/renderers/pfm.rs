@@ -0,0 +1,52 @@
+use std::io::{self, Write};
+
+use super::{Image, Output};
+
+/// Writes the linear radiance as a Portable Float Map
+pub struct PFM {
+    output: Output,
+    grayscale: bool,
+}
+
+output_constructors!(PFM, "an RGB (`PF`) image");
+
+impl PFM {
+    fn with_output(output: Output) -> Self {
+        PFM {
+            output,
+            grayscale: false,
+        }
+    }
+
+    /// Writes a single channel (`Pf`) image holding the luminance instead of RGB
+    pub fn set_grayscale(&mut self, grayscale: bool) {
+        self.grayscale = grayscale;
+    }
+}
+
+impl super::Renderer for PFM {
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let mut out = self.output.open()?;
+        let magic = if self.grayscale { "Pf" } else { "PF" };
+        // The negative scale marks the samples as little-endian
+        write!(out, "{}\n{} {}\n-1.0\n", magic, image.width, image.height)?;
+
+        let mut line = Vec::new();
+        // Rows are stored from the bottom of the image to the top
+        for row in image.pixels().chunks_exact(image.width.max(1)).rev() {
+            line.clear();
+            for pixel in row {
+                if self.grayscale {
+                    let luminance = 0.2126 * pixel.x + 0.7152 * pixel.y + 0.0722 * pixel.z;
+                    line.extend_from_slice(&(luminance as f32).to_le_bytes());
+                } else {
+                    for sample in [pixel.x, pixel.y, pixel.z] {
+                        line.extend_from_slice(&(sample as f32).to_le_bytes());
+                    }
+                }
+            }
+            out.write_all(&line)?;
+        }
+        out.flush()
+    }
+}
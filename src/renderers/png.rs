@@ -0,0 +1,210 @@
+use std::io::{self, Write};
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use super::{Image, Output, ToneMapper};
+use crate::{
+    loaders::png::paeth,
+    math::{color, Vec3},
+};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Compression level passed to miniz, from 0 (none) to 10 (smallest and slowest)
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Number of bits per sample
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    /// Keeps smooth gradients free of banding after further editing
+    Sixteen,
+}
+
+pub struct PNG {
+    output: Output,
+    bit_depth: BitDepth,
+    alpha: bool,
+    tone_mapper: ToneMapper,
+}
+
+output_constructors!(PNG, "an 8-bit RGB PNG image");
+
+impl PNG {
+    fn with_output(output: Output) -> Self {
+        PNG {
+            output,
+            bit_depth: BitDepth::default(),
+            alpha: false,
+            tone_mapper: ToneMapper::default(),
+        }
+    }
+
+    pub fn set_bit_depth(&mut self, bit_depth: BitDepth) {
+        self.bit_depth = bit_depth;
+    }
+
+    /// Adds an alpha channel. Every pixel of a render is covered, so it is fully opaque.
+    pub fn set_alpha(&mut self, alpha: bool) {
+        self.alpha = alpha;
+    }
+
+    /// Sets how the HDR pixels are brought into the displayable range: the working space of the
+    /// image, the exposure and the tone mapping operator
+    pub fn set_tone_mapper(&mut self, tone_mapper: ToneMapper) {
+        self.tone_mapper = tone_mapper;
+    }
+
+    fn channels(&self) -> usize {
+        if self.alpha {
+            4
+        } else {
+            3
+        }
+    }
+
+    fn bytes_per_sample(&self) -> usize {
+        match self.bit_depth {
+            BitDepth::Eight => 1,
+            BitDepth::Sixteen => 2,
+        }
+    }
+
+    /// Encodes a row of pixels into big-endian samples
+    fn encode_row(&self, row: &[Vec3], line: &mut Vec<u8>) {
+        for pixel in row {
+            match self.bit_depth {
+                BitDepth::Eight => {
+                    let color = self.tone_mapper.to_color(*pixel);
+                    line.extend_from_slice(&[color.r, color.g, color.b]);
+                    if self.alpha {
+                        line.push(u8::MAX);
+                    }
+                }
+                BitDepth::Sixteen => {
+                    let c = self.tone_mapper.map(*pixel);
+                    for sample in [c.x, c.y, c.z] {
+                        let sample = color::srgb_encode(sample).clamp(0.0, 1.0);
+                        line.extend_from_slice(&((sample * 65535.0).round() as u16).to_be_bytes());
+                    }
+                    if self.alpha {
+                        line.extend_from_slice(&u16::MAX.to_be_bytes());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Filters every scanline and prefixes it with its filter type. Each line uses the filter
+    /// with the smallest sum of absolute differences, which usually compresses best.
+    fn filtered_scanlines(&self, image: &Image) -> Vec<u8> {
+        let bpp = self.channels() * self.bytes_per_sample();
+        let line_bytes = image.width * bpp;
+        let mut data = Vec::with_capacity((line_bytes + 1) * image.height);
+        let mut prev = vec![0u8; line_bytes];
+        let mut line = Vec::with_capacity(line_bytes);
+        let mut candidate = vec![0u8; line_bytes];
+        let mut best = vec![0u8; line_bytes];
+
+        for row in image.pixels().chunks_exact(image.width.max(1)) {
+            line.clear();
+            self.encode_row(row, &mut line);
+
+            let mut best_filter = 0;
+            let mut best_score = u64::MAX;
+            for filter in 0..5u8 {
+                for i in 0..line_bytes {
+                    let a = if i >= bpp { line[i - bpp] } else { 0 };
+                    let b = prev[i];
+                    let c = if i >= bpp { prev[i - bpp] } else { 0 };
+                    let predictor = match filter {
+                        0 => 0,
+                        1 => a,
+                        2 => b,
+                        3 => ((a as u16 + b as u16) / 2) as u8,
+                        _ => paeth(a, b, c),
+                    };
+                    candidate[i] = line[i].wrapping_sub(predictor);
+                }
+                // Treat the bytes as signed so that small negative residuals score low too
+                let score = candidate
+                    .iter()
+                    .map(|&x| (x as i8).unsigned_abs() as u64)
+                    .sum();
+                if score < best_score {
+                    best_score = score;
+                    best_filter = filter;
+                    best.copy_from_slice(&candidate);
+                }
+            }
+
+            data.push(best_filter);
+            data.extend_from_slice(&best);
+            prev.copy_from_slice(&line);
+        }
+        data
+    }
+}
+
+impl super::Renderer for PNG {
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(image.width as u32).to_be_bytes());
+        header.extend_from_slice(&(image.height as u32).to_be_bytes());
+        header.push(match self.bit_depth {
+            BitDepth::Eight => 8,
+            BitDepth::Sixteen => 16,
+        });
+        // Color type 2 is RGB and 6 is RGB with alpha
+        header.push(if self.alpha { 6 } else { 2 });
+        // Deflate compression, adaptive filtering, no interlacing
+        header.extend_from_slice(&[0, 0, 0]);
+
+        let compressed = compress_to_vec_zlib(&self.filtered_scanlines(image), COMPRESSION_LEVEL);
+
+        let mut out = self.output.open()?;
+        out.write_all(&SIGNATURE)?;
+        write_chunk(&mut out, b"IHDR", &header)?;
+        // The pixels are sRGB encoded with the perceptual rendering intent
+        write_chunk(&mut out, b"sRGB", &[0])?;
+        write_chunk(&mut out, b"IDAT", &compressed)?;
+        write_chunk(&mut out, b"IEND", &[])?;
+        out.flush()
+    }
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], body: &[u8]) -> io::Result<()> {
+    out.write_all(&(body.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(body)?;
+    // The CRC covers the chunk type and the body but not the length
+    let crc = crc32_update(crc32_update(0xffff_ffff, kind), body) ^ 0xffff_ffff;
+    out.write_all(&crc.to_be_bytes())
+}
+
+/// Lookup table of the CRC-32 used by PNG (polynomial `0xedb88320`, reflected)
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
@@ -1,35 +1,77 @@
-use std::fs;
+use std::io::{self, Write};
 
-use super::Image;
+use super::{Image, Output, ToneMapper};
 
-#[derive(Default)]
-pub struct PPM {}
+/// The two encodings of the PPM format
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// `P3`, human readable decimal samples
+    Ascii,
+    /// `P6`, one byte per sample. A lot smaller and faster to write than `P3`.
+    #[default]
+    Binary,
+}
+
+pub struct PPM {
+    output: Output,
+    format: PpmFormat,
+    tone_mapper: ToneMapper,
+}
+
+output_constructors!(PPM, "a binary PPM image");
 
 impl PPM {
-    pub fn new() -> Self {
-        PPM {}
+    fn with_output(output: Output) -> Self {
+        PPM {
+            output,
+            format: PpmFormat::default(),
+            tone_mapper: ToneMapper::default(),
+        }
     }
-}
 
-const OUTPUT_FILE: &str = "test.ppm";
+    pub fn set_format(&mut self, format: PpmFormat) {
+        self.format = format;
+    }
+
+    /// Sets how the HDR pixels are brought into the displayable range: the working space of the
+    /// image, the exposure and the tone mapping operator
+    pub fn set_tone_mapper(&mut self, tone_mapper: ToneMapper) {
+        self.tone_mapper = tone_mapper;
+    }
+}
 
 impl super::Renderer for PPM {
-    fn draw(self, image: &Image) {
-        let mut buffer = String::new();
+    fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let mut out = self.output.open()?;
 
-        // Header
-        buffer += format!("P3\n{} {}\n255\n", image.width, image.height).as_str();
+        let magic = match self.format {
+            PpmFormat::Ascii => "P3",
+            PpmFormat::Binary => "P6",
+        };
+        write!(out, "{}\n{} {}\n255\n", magic, image.width, image.height)?;
 
-        for y in 0..image.height {
-            for x in 0..image.width {
-                let color = image.get_pixel(x, y);
-                buffer += format!("{} {} {} ", color.r, color.g, color.b).as_str();
+        for row in image.pixels().chunks_exact(image.width.max(1)) {
+            match self.format {
+                PpmFormat::Ascii => {
+                    for pixel in row {
+                        let color = self.tone_mapper.to_color(*pixel);
+                        write!(out, "{} {} {} ", color.r, color.g, color.b)?;
+                    }
+                    writeln!(out)?;
+                }
+                PpmFormat::Binary => {
+                    let bytes: Vec<u8> = row
+                        .iter()
+                        .flat_map(|pixel| {
+                            let color = self.tone_mapper.to_color(*pixel);
+                            [color.r, color.g, color.b]
+                        })
+                        .collect();
+                    out.write_all(&bytes)?;
+                }
             }
-            buffer += "\n";
         }
 
-        if let Err(e) = fs::write(OUTPUT_FILE, buffer) {
-            panic!("Error writing to file: {}", e);
-        }
+        out.flush()
     }
 }
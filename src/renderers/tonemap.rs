@@ -0,0 +1,129 @@
+use super::Color;
+use crate::math::{color::ColorSpace, Vec3};
+
+/// Operators that compress linear HDR radiance into the `[0, 1]` range of display colors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapping {
+    /// Cuts off everything above 1.0, which blows out bright highlights
+    Clamp,
+    /// `c / (1 + c)` per channel. Never reaches 1.0, so nothing is fully white.
+    Reinhard,
+    /// Reinhard extended so that the radiance `white` (and everything above it) maps to 1.0
+    ExtendedReinhard { white: f64 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic reference rendering transform
+    AcesFilmic,
+    /// John Hable's filmic curve from Uncharted 2
+    Uncharted2,
+}
+
+/// Linear white point of the Uncharted 2 curve
+const UNCHARTED2_WHITE: f64 = 11.2;
+
+impl ToneMapping {
+    /// Maps a single linear channel
+    fn map_channel(&self, x: f64) -> f64 {
+        let x = x.max(0.0);
+        match *self {
+            ToneMapping::Clamp => x.min(1.0),
+            ToneMapping::Reinhard => x / (1.0 + x),
+            ToneMapping::ExtendedReinhard { white } => {
+                (x * (1.0 + x / (white * white)) / (1.0 + x)).min(1.0)
+            }
+            ToneMapping::AcesFilmic => {
+                ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+            }
+            ToneMapping::Uncharted2 => {
+                // The curve expects the exposure bias of 2 used in the game
+                (uncharted2_partial(2.0 * x) / uncharted2_partial(UNCHARTED2_WHITE)).min(1.0)
+            }
+        }
+    }
+
+    pub fn apply(&self, c: Vec3) -> Vec3 {
+        Vec3::new(
+            self.map_channel(c.x),
+            self.map_channel(c.y),
+            self.map_channel(c.z),
+        )
+    }
+}
+
+fn uncharted2_partial(x: f64) -> f64 {
+    const A: f64 = 0.15; // Shoulder strength
+    const B: f64 = 0.50; // Linear strength
+    const C: f64 = 0.10; // Linear angle
+    const D: f64 = 0.20; // Toe strength
+    const E: f64 = 0.02; // Toe numerator
+    const F: f64 = 0.30; // Toe denominator
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+}
+
+/// The conversion stage of LDR renderers: scales the radiance by the exposure and compresses it
+/// with the tone mapping operator before it is sRGB encoded and quantized to 8 bits
+#[derive(Clone, Copy, Debug)]
+pub struct ToneMapper {
+    operator: ToneMapping,
+    /// Working space of the rendered image, converted to sRGB for display
+    color_space: ColorSpace,
+    /// Exposure in stops, every stop doubles the brightness
+    exposure: f64,
+    /// Converts the working space to sRGB and applies the exposure. Recomputed whenever either
+    /// of them changes, so mapping a pixel is a single matrix product.
+    to_display: [[f64; 3]; 3],
+}
+
+impl ToneMapper {
+    pub fn new(operator: ToneMapping) -> Self {
+        let mut mapper = Self {
+            operator,
+            color_space: ColorSpace::Srgb,
+            exposure: 0.0,
+            to_display: [[0.0; 3]; 3],
+        };
+        mapper.update_matrix();
+        mapper
+    }
+
+    pub fn set_operator(&mut self, operator: ToneMapping) {
+        self.operator = operator;
+    }
+
+    /// Sets the working space the image was rendered in
+    pub fn set_color_space(&mut self, color_space: ColorSpace) {
+        self.color_space = color_space;
+        self.update_matrix();
+    }
+
+    /// Sets the exposure in stops. `1.0` doubles the brightness, `-1.0` halves it.
+    pub fn set_exposure(&mut self, stops: f64) {
+        self.exposure = stops;
+        self.update_matrix();
+    }
+
+    fn update_matrix(&mut self) {
+        let scale = 2f64.powf(self.exposure);
+        self.to_display = self
+            .color_space
+            .conversion_to(ColorSpace::Srgb)
+            .map(|row| row.map(|value| value * scale));
+    }
+
+    /// Converts the color to sRGB and applies the exposure and the operator. The result is linear
+    /// and within `[0, 1]`.
+    pub fn map(&self, c: Vec3) -> Vec3 {
+        let [r, g, b] = self
+            .to_display
+            .map(|row| row[0] * c.x + row[1] * c.y + row[2] * c.z);
+        self.operator.apply(Vec3::new(r, g, b))
+    }
+
+    pub fn to_color(&self, c: Vec3) -> Color {
+        self.map(c).into()
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        Self::new(ToneMapping::Clamp)
+    }
+}
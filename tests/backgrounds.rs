@@ -0,0 +1,102 @@
+use zharko::{
+    math::{
+        backgrounds::{Background, EnvironmentMap},
+        Ray, Vec3,
+    },
+    renderers::Image,
+};
+
+mod common;
+
+use common::assert_near;
+
+/// A 4x2 panorama where every pixel has a different color
+fn panorama() -> EnvironmentMap {
+    let mut image = Image::new(4, 2);
+    for y in 0..2 {
+        for x in 0..4 {
+            image.set_pixel(x, y, Vec3::new(x as f64, y as f64, (x + 4 * y) as f64));
+        }
+    }
+    EnvironmentMap::new(image)
+}
+
+fn color(map: &EnvironmentMap, dir: Vec3) -> Vec3 {
+    map.color(&Ray::new(Vec3::zero(), dir))
+}
+
+/// The average of the 2x2 pixels starting at column `x`
+fn average_of_columns(x0: usize, x1: usize) -> Vec3 {
+    let pixel = |x: usize, y: usize| Vec3::new(x as f64, y as f64, (x + 4 * y) as f64);
+    0.25 * (pixel(x0, 0) + pixel(x1, 0) + pixel(x0, 1) + pixel(x1, 1))
+}
+
+#[test]
+fn forward_is_the_center_of_the_image() {
+    // The center of the image lies between the four middle pixels
+    let map = panorama();
+    assert_near(
+        color(&map, Vec3::new(0.0, 0.0, -1.0)),
+        average_of_columns(1, 2),
+    );
+}
+
+#[test]
+fn backward_wraps_around_the_seam() {
+    let map = panorama();
+    assert_near(
+        color(&map, Vec3::new(0.0, 0.0, 1.0)),
+        average_of_columns(3, 0),
+    );
+}
+
+#[test]
+fn bilinear_interpolation_between_pixel_centers() {
+    // Straight right is a quarter of the image right of the center, between columns 2 and 3
+    let map = panorama();
+    assert_near(
+        color(&map, Vec3::new(1.0, 0.0, 0.0)),
+        average_of_columns(2, 3),
+    );
+    // Halfway between right and backward hits the centers of column 3 exactly
+    assert_near(
+        color(&map, Vec3::new(1.0, 0.0, 1.0)),
+        0.5 * (Vec3::new(3.0, 0.0, 3.0) + Vec3::new(3.0, 1.0, 7.0)),
+    );
+}
+
+#[test]
+fn poles_are_clamped_to_the_outer_rows() {
+    let map = panorama();
+    let up = color(&map, Vec3::new(0.0, 1.0, -1e-9));
+    assert_near(
+        up,
+        0.5 * (Vec3::new(1.0, 0.0, 1.0) + Vec3::new(2.0, 0.0, 2.0)),
+    );
+    let down = color(&map, Vec3::new(0.0, -1.0, -1e-9));
+    assert_near(
+        down,
+        0.5 * (Vec3::new(1.0, 1.0, 5.0) + Vec3::new(2.0, 1.0, 6.0)),
+    );
+}
+
+#[test]
+fn rotation_turns_the_map_counter_clockwise() {
+    // Turned by 90 degrees, the view direction sees what was on its left
+    let mut map = panorama();
+    map.set_rotation(90.0);
+    assert_near(
+        color(&map, Vec3::new(0.0, 0.0, -1.0)),
+        average_of_columns(0, 1),
+    );
+    assert_near(
+        color(&map, Vec3::new(1.0, 0.0, 0.0)),
+        color(&panorama(), Vec3::new(0.0, 0.0, -1.0)),
+    );
+}
+
+#[test]
+fn empty_map_is_black() {
+    let map = EnvironmentMap::new(Image::new(0, 0));
+    assert_near(color(&map, Vec3::new(0.0, 0.0, -1.0)), Vec3::zero());
+}
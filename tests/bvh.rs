@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use zharko::math::{
+    hittables::{BvhNode, HittableList, Sphere},
+    interval::Interval,
+    materials::Lambertian,
+    HitResult, Hittable, Ray, Vec3,
+};
+
+/// Builds the same random scene twice so that one copy can be turned into a BVH
+fn random_spheres(seed: u64, count: usize) -> (HittableList, HittableList) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    let mut a = HittableList::new();
+    let mut b = HittableList::new();
+    for _ in 0..count {
+        let center = Vec3::new(
+            rng.random_range(-20.0..20.0),
+            rng.random_range(-20.0..20.0),
+            rng.random_range(-20.0..20.0),
+        );
+        let radius = rng.random_range(0.1..2.0);
+        a.add(Box::new(Sphere::new(center, radius, material.clone())));
+        b.add(Box::new(Sphere::new(center, radius, material.clone())));
+    }
+    (a, b)
+}
+
+fn assert_same_hits(list: &HittableList, bvh: &BvhNode, seed: u64, rays: usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..rays {
+        let origin = Vec3::new(
+            rng.random_range(-30.0..30.0),
+            rng.random_range(-30.0..30.0),
+            rng.random_range(-30.0..30.0),
+        );
+        let dir = Vec3::new(
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+        );
+        let ray = Ray::new(origin, dir);
+        let ray_t = Interval::new(0.0001, f64::INFINITY);
+
+        match (list.hit(&ray, ray_t), bvh.hit(&ray, ray_t)) {
+            (HitResult::NoHit, HitResult::NoHit) => (),
+            (HitResult::Hit(expected), HitResult::Hit(actual)) => {
+                assert_eq!(expected.t, actual.t);
+                assert_eq!(expected.front_face, actual.front_face);
+                assert!((expected.point - actual.point).near_zero());
+                assert!((expected.normal - actual.normal).near_zero());
+            }
+            _ => panic!("BVH and list disagree on whether {:?} hits", ray.dir),
+        }
+    }
+}
+
+#[test]
+fn bvh_matches_list() {
+    let (list, objects) = random_spheres(1, 1000);
+    let bvh = BvhNode::new(objects);
+    assert_same_hits(&list, &bvh, 2, 5000);
+}
+
+#[test]
+fn bvh_matches_list_with_coincident_objects() {
+    let material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    let mut list = HittableList::new();
+    let mut objects = HittableList::new();
+    for i in 0..20 {
+        let radius = 0.5 + i as f64 * 0.1;
+        list.add(Box::new(Sphere::new(
+            Vec3::zero(),
+            radius,
+            material.clone(),
+        )));
+        objects.add(Box::new(Sphere::new(
+            Vec3::zero(),
+            radius,
+            material.clone(),
+        )));
+    }
+    let bvh = BvhNode::new(objects);
+    assert_same_hits(&list, &bvh, 3, 1000);
+}
+
+#[test]
+fn bvh_bounding_box_encloses_list() {
+    let (list, objects) = random_spheres(4, 100);
+    let bvh = BvhNode::new(objects);
+    let expected = list.bounding_box();
+    let actual = bvh.bounding_box();
+    for (e, a) in [
+        (expected.x, actual.x),
+        (expected.y, actual.y),
+        (expected.z, actual.z),
+    ] {
+        assert_eq!(e.min, a.min);
+        assert_eq!(e.max, a.max);
+    }
+}
+
+#[test]
+fn empty_bvh_never_hits() {
+    let bvh = BvhNode::new(HittableList::new());
+    let ray = Ray::new(Vec3::zero(), Vec3::new(0.0, 0.0, -1.0));
+    assert!(matches!(
+        bvh.hit(&ray, Interval::new(0.0001, f64::INFINITY)),
+        HitResult::NoHit
+    ));
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use zharko::{
+    camera::{CameraBuilder, CameraError, FisheyeMapping, Projection},
+    math::{
+        backgrounds::SolidBackground,
+        hittables::{HittableList, Sphere},
+        materials::Lambertian,
+        Vec3,
+    },
+};
+
+fn build_error(builder: CameraBuilder) -> CameraError {
+    match builder.build() {
+        Err(e) => e,
+        Ok(_) => panic!("expected the camera settings to be rejected"),
+    }
+}
+
+fn fisheye(fov: f64) -> Projection {
+    Projection::Fisheye {
+        fov,
+        mapping: FisheyeMapping::Equidistant,
+    }
+}
+
+#[test]
+fn valid_camera_renders_the_background() {
+    let camera = CameraBuilder::new()
+        .resolution(3, 2)
+        .samples_per_pixel(1)
+        .threads(1)
+        .background(SolidBackground::new(Vec3::new(0.25, 0.5, 1.0)))
+        .build()
+        .unwrap();
+    let image = camera.render(&HittableList::new());
+    assert_eq!((image.width, image.height), (3, 2));
+    for pixel in image.pixels() {
+        assert!((*pixel - Vec3::new(0.25, 0.5, 1.0)).length() < 1e-12);
+    }
+}
+
+#[test]
+fn zero_resolution_is_rejected() {
+    assert_eq!(
+        build_error(CameraBuilder::new().resolution(0, 10)),
+        CameraError::ZeroResolution {
+            width: 0,
+            height: 10
+        }
+    );
+    assert_eq!(
+        build_error(CameraBuilder::new().resolution(10, 0)),
+        CameraError::ZeroResolution {
+            width: 10,
+            height: 0
+        }
+    );
+    assert_eq!(
+        build_error(CameraBuilder::new().aspect_ratio(0, 1.5)),
+        CameraError::ZeroResolution {
+            width: 0,
+            height: 1
+        }
+    );
+}
+
+#[test]
+fn invalid_aspect_ratio_is_rejected() {
+    assert_eq!(
+        build_error(CameraBuilder::new().aspect_ratio(100, -1.0)),
+        CameraError::InvalidAspectRatio(-1.0)
+    );
+    assert!(matches!(
+        build_error(CameraBuilder::new().aspect_ratio(100, f64::NAN)),
+        CameraError::InvalidAspectRatio(ratio) if ratio.is_nan()
+    ));
+}
+
+#[test]
+fn invalid_field_of_view_is_rejected() {
+    for vfov in [0.0, 180.0, -10.0] {
+        assert_eq!(
+            build_error(CameraBuilder::new().vfov(vfov)),
+            CameraError::InvalidFieldOfView(vfov)
+        );
+    }
+}
+
+#[test]
+fn missing_view_direction_is_rejected() {
+    let builder = CameraBuilder::new()
+        .lookfrom(Vec3::new(1.0, 2.0, 3.0))
+        .lookat(Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(build_error(builder), CameraError::ZeroViewDirection);
+}
+
+#[test]
+fn vup_parallel_to_the_view_is_rejected() {
+    // The default view direction is down the negative `z` axis
+    for vup in [
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(0.0, 0.0, -2.0),
+        Vec3::zero(),
+    ] {
+        assert_eq!(
+            build_error(CameraBuilder::new().vup(vup)),
+            CameraError::VupParallelToView
+        );
+    }
+    // Not perpendicular, but not parallel either
+    assert!(CameraBuilder::new()
+        .vup(Vec3::new(0.0, 1.0, 1.0))
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn invalid_focus_distance_is_rejected() {
+    for dist in [0.0, -1.0, f64::INFINITY] {
+        assert_eq!(
+            build_error(CameraBuilder::new().focus_dist(dist)),
+            CameraError::InvalidFocusDistance(dist)
+        );
+    }
+    assert!(matches!(
+        build_error(CameraBuilder::new().focus_dist(f64::NAN)),
+        CameraError::InvalidFocusDistance(dist) if dist.is_nan()
+    ));
+}
+
+#[test]
+fn invalid_defocus_angle_is_rejected() {
+    for angle in [-1.0, 180.0] {
+        assert_eq!(
+            build_error(CameraBuilder::new().defocus_angle(angle)),
+            CameraError::InvalidDefocusAngle(angle)
+        );
+    }
+}
+
+#[test]
+fn zero_samples_are_rejected() {
+    assert_eq!(
+        build_error(CameraBuilder::new().samples_per_pixel(0)),
+        CameraError::ZeroSamples
+    );
+}
+
+#[test]
+fn invalid_max_depth_is_rejected() {
+    for depth in [0, -3] {
+        assert_eq!(
+            build_error(CameraBuilder::new().max_depth(depth)),
+            CameraError::InvalidMaxDepth(depth)
+        );
+    }
+}
+
+#[test]
+fn invalid_orthographic_view_height_is_rejected() {
+    for view_height in [0.0, -2.0, f64::INFINITY] {
+        let builder = CameraBuilder::new().projection(Projection::Orthographic { view_height });
+        assert_eq!(
+            build_error(builder),
+            CameraError::InvalidViewHeight(view_height)
+        );
+    }
+    let builder = CameraBuilder::new().projection(Projection::Orthographic { view_height: 4.0 });
+    assert!(builder.build().is_ok());
+}
+
+#[test]
+fn invalid_fisheye_field_of_view_is_rejected() {
+    for fov in [0.0, -90.0, 360.5] {
+        assert_eq!(
+            build_error(CameraBuilder::new().projection(fisheye(fov))),
+            CameraError::InvalidFisheyeFieldOfView(fov)
+        );
+    }
+    // A full sphere is the widest fisheye
+    assert!(CameraBuilder::new()
+        .projection(fisheye(360.0))
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn non_orthogonal_vup_gives_the_same_image() {
+    // A sphere to the right of the view direction, which moves if the viewport is stretched
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(1.2, -1.0, -1.0),
+        0.3,
+        Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    )));
+    // Looking down at 45 degrees
+    let depth = |vup: Vec3| {
+        let camera = CameraBuilder::new()
+            .resolution(16, 8)
+            .lookat(Vec3::new(0.0, -1.0, -1.0))
+            .vup(vup)
+            .build()
+            .unwrap();
+        camera.render_aux_buffers(&world).depth
+    };
+    // Perpendicular to the view direction
+    let orthogonal = depth(Vec3::new(0.0, 1.0, -1.0));
+    assert!(orthogonal.iter().any(|d| d.is_finite()));
+    // The default up vector and a longer one point the same way as seen by the camera
+    for vup in [Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 5.0, 0.0)] {
+        for (a, b) in orthogonal.iter().zip(&depth(vup)) {
+            assert!(a == b || (a - b).abs() < 1e-9, "{} != {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use zharko::{
+    math::{
+        color::{srgb_decode, srgb_encode, ColorSpace},
+        Vec3,
+    },
+    renderers::{ToneMapper, ToneMapping},
+};
+
+mod common;
+
+use common::assert_within;
+
+const SPACES: [ColorSpace; 3] = [ColorSpace::Srgb, ColorSpace::Rec2020, ColorSpace::AcesCg];
+
+#[test]
+fn srgb_round_trip() {
+    for i in 0..=1000 {
+        let x = i as f64 / 1000.0;
+        assert!((srgb_decode(srgb_encode(x)) - x).abs() < 1e-12, "{}", x);
+        assert!((srgb_encode(srgb_decode(x)) - x).abs() < 1e-12, "{}", x);
+    }
+    assert_eq!(srgb_encode(0.0), 0.0);
+    assert!((srgb_encode(1.0) - 1.0).abs() < 1e-12);
+    assert!((srgb_decode(1.0) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn srgb_breakpoints_are_continuous() {
+    // The linear segment and the power curve meet at the breakpoints
+    assert!((srgb_encode(0.0031308) - 0.04045).abs() < 1e-6);
+    assert!((srgb_encode(0.0031308 + 1e-9) - 0.04045).abs() < 1e-6);
+    assert!((srgb_decode(0.04045) - 0.0031308).abs() < 1e-6);
+    assert!((srgb_decode(0.04045 + 1e-9) - 0.0031308).abs() < 1e-6);
+    // The linear segment
+    assert!((srgb_encode(0.001) - 0.01292).abs() < 1e-12);
+    assert!((srgb_decode(0.01292) - 0.001).abs() < 1e-12);
+}
+
+#[test]
+fn negative_values_are_clamped() {
+    assert_eq!(srgb_encode(-0.5), 0.0);
+    assert_eq!(srgb_decode(-0.5), 0.0);
+}
+
+#[test]
+fn conversion_to_the_same_space_is_the_identity() {
+    let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let c = Vec3::new(0.2, 0.5, 3.0);
+    for space in SPACES {
+        assert_eq!(space.conversion_to(space), identity);
+        assert_within(space.convert(c, space), c, 1e-15);
+    }
+}
+
+#[test]
+fn conversions_round_trip() {
+    let c = Vec3::new(0.2, 0.5, 3.0);
+    for from in SPACES {
+        for to in SPACES {
+            assert_within(to.convert(from.convert(c, to), from), c, 1e-12);
+        }
+    }
+}
+
+#[test]
+fn white_is_preserved() {
+    let white = Vec3::new(1.0, 1.0, 1.0);
+    let rec2020 = ColorSpace::Srgb.convert(white, ColorSpace::Rec2020);
+    assert_within(rec2020, white, 1e-12);
+    assert_within(
+        ColorSpace::Rec2020.convert(rec2020, ColorSpace::Srgb),
+        white,
+        1e-12,
+    );
+}
+
+#[test]
+fn acescg_adapts_the_white_point() {
+    // Bradford adaptation maps the D65 white of sRGB onto the white of ACES, so white stays
+    // white even though the white points differ
+    let white = Vec3::new(1.0, 1.0, 1.0);
+    assert_within(
+        ColorSpace::Srgb.convert(white, ColorSpace::AcesCg),
+        white,
+        1e-9,
+    );
+
+    // The published sRGB to ACEScg matrix
+    let expected = [
+        [0.613097, 0.339523, 0.047379],
+        [0.070194, 0.916354, 0.013452],
+        [0.020616, 0.109570, 0.869815],
+    ];
+    let matrix = ColorSpace::Srgb.conversion_to(ColorSpace::AcesCg);
+    for (row, expected_row) in matrix.iter().zip(expected) {
+        for (value, expected) in row.iter().zip(expected_row) {
+            assert!((value - expected).abs() < 1e-3, "{:?}", matrix);
+        }
+    }
+}
+
+#[test]
+fn tone_mapper_converts_and_exposes() {
+    let c = Vec3::new(0.1, 0.2, 0.05);
+    let mut mapper = ToneMapper::new(ToneMapping::Clamp);
+    assert_within(mapper.map(c), c, 1e-12);
+
+    mapper.set_color_space(ColorSpace::AcesCg);
+    mapper.set_exposure(1.0);
+    let expected = 2.0 * ColorSpace::AcesCg.convert(c, ColorSpace::Srgb);
+    assert_within(mapper.map(c), expected, 1e-12);
+
+    // Setting the space back restores the identity conversion
+    mapper.set_color_space(ColorSpace::Srgb);
+    mapper.set_exposure(0.0);
+    assert_within(mapper.map(c), c, 1e-12);
+}
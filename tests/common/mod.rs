@@ -0,0 +1,51 @@
+//! Helpers shared by the integration tests. Every test crate uses a different subset of them.
+#![allow(dead_code)]
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use zharko::{loaders::LoadError, math::Vec3};
+
+/// A writer whose contents can still be read after the renderer that owns it is done
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Everything written so far
+    pub fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn assert_format_error<T>(result: Result<T, LoadError>) {
+    match result {
+        Err(LoadError::Format(_)) => (),
+        Err(e) => panic!("expected a format error, got {}", e),
+        Ok(_) => panic!("expected a format error"),
+    }
+}
+
+pub fn assert_near(actual: Vec3, expected: Vec3) {
+    assert_within(actual, expected, 1e-9);
+}
+
+pub fn assert_within(actual: Vec3, expected: Vec3, tolerance: f64) {
+    assert!(
+        (actual - expected).length() < tolerance,
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
@@ -0,0 +1,371 @@
+use std::io;
+
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use zharko::{
+    math::Vec3,
+    renderers::{Compression, Image, PixelType, Renderer, EXR},
+};
+
+mod common;
+
+use common::SharedBuffer;
+
+const WIDTH: usize = 8;
+const HEIGHT: usize = 20;
+
+/// An image with flat areas, so that every compression makes the blocks smaller. All of its
+/// values are exact in half precision.
+fn image() -> Image {
+    let mut image = Image::new(WIDTH, HEIGHT);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let pixel = if x < WIDTH / 2 {
+                Vec3::new(0.5, 1.0, 2.0)
+            } else {
+                Vec3::new(0.25, 0.0, y as f64)
+            };
+            image.set_pixel(x, y, pixel);
+        }
+    }
+    image
+}
+
+fn encode(mut renderer: EXR, buffer: &SharedBuffer) -> Vec<u8> {
+    renderer.draw(&image()).unwrap();
+    buffer.contents()
+}
+
+fn encode_with(compression: Compression) -> Vec<u8> {
+    let buffer = SharedBuffer::default();
+    let mut renderer = EXR::from_writer(buffer.clone());
+    renderer.set_compression(compression);
+    encode(renderer, &buffer)
+}
+
+struct Attribute {
+    name: String,
+    kind: String,
+    value: Vec<u8>,
+}
+
+struct Block {
+    first_line: i32,
+    data: Vec<u8>,
+}
+
+fn read_string(file: &[u8], pos: &mut usize) -> String {
+    let end = *pos + file[*pos..].iter().position(|&b| b == 0).unwrap();
+    let string = String::from_utf8(file[*pos..end].to_vec()).unwrap();
+    *pos = end + 1;
+    string
+}
+
+fn read_i32(file: &[u8], pos: usize) -> i32 {
+    i32::from_le_bytes(file[pos..pos + 4].try_into().unwrap())
+}
+
+/// Splits a file into its header attributes and the blocks the offset table points at
+fn parse(file: &[u8], block_count: usize) -> (Vec<Attribute>, Vec<Block>) {
+    assert_eq!(file[..4], [0x76, 0x2f, 0x31, 0x01]);
+    assert_eq!(file[4..8], [2, 0, 0, 0]);
+
+    let mut pos = 8;
+    let mut attributes = Vec::new();
+    while file[pos] != 0 {
+        let name = read_string(file, &mut pos);
+        let kind = read_string(file, &mut pos);
+        let size = read_i32(file, pos) as usize;
+        let value = file[pos + 4..pos + 4 + size].to_vec();
+        pos += 4 + size;
+        attributes.push(Attribute { name, kind, value });
+    }
+    pos += 1;
+
+    let offsets: Vec<usize> = (0..block_count)
+        .map(|i| {
+            u64::from_le_bytes(file[pos + 8 * i..pos + 8 * i + 8].try_into().unwrap()) as usize
+        })
+        .collect();
+    // The first block directly follows the table, and every block the one before it
+    let mut expected_offset = pos + 8 * block_count;
+    let mut blocks = Vec::new();
+    for offset in offsets {
+        assert_eq!(offset, expected_offset);
+        let size = read_i32(file, offset + 4) as usize;
+        blocks.push(Block {
+            first_line: read_i32(file, offset),
+            data: file[offset + 8..offset + 8 + size].to_vec(),
+        });
+        expected_offset = offset + 8 + size;
+    }
+    assert_eq!(expected_offset, file.len());
+    (attributes, blocks)
+}
+
+fn attribute<'a>(attributes: &'a [Attribute], name: &str) -> &'a Attribute {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .unwrap_or_else(|| panic!("missing attribute {}", name))
+}
+
+/// Builds the expected channel list for channels with the same pixel type
+fn chlist(names: &[&str], pixel_type: i32) -> Vec<u8> {
+    let mut chlist = Vec::new();
+    for name in names {
+        chlist.extend_from_slice(name.as_bytes());
+        chlist.push(0);
+        chlist.extend_from_slice(&pixel_type.to_le_bytes());
+        chlist.extend_from_slice(&[0, 0, 0, 0]);
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+    }
+    chlist.push(0);
+    chlist
+}
+
+/// Undoes the prediction and the byte interleaving of a decompressed block
+fn reconstruct(data: &[u8]) -> Vec<u8> {
+    let mut predicted = data.to_vec();
+    for i in 1..predicted.len() {
+        predicted[i] = predicted[i - 1]
+            .wrapping_add(predicted[i])
+            .wrapping_sub(128);
+    }
+    let half = predicted.len().div_ceil(2);
+    let mut raw = Vec::with_capacity(predicted.len());
+    for i in 0..half {
+        raw.push(predicted[i]);
+        if half + i < predicted.len() {
+            raw.push(predicted[half + i]);
+        }
+    }
+    raw
+}
+
+fn rle_decompress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let count = data[pos] as i8;
+        if count >= 0 {
+            out.extend(std::iter::repeat_n(data[pos + 1], count as usize + 1));
+            pos += 2;
+        } else {
+            let literals = -(count as isize) as usize;
+            out.extend_from_slice(&data[pos + 1..pos + 1 + literals]);
+            pos += 1 + literals;
+        }
+    }
+    out
+}
+
+fn decompress(compression: Compression, data: &[u8], raw_size: usize) -> Vec<u8> {
+    // Blocks that didn't get smaller are stored as they are
+    if data.len() == raw_size {
+        return data.to_vec();
+    }
+    match compression {
+        Compression::None => panic!("uncompressed block of the wrong size"),
+        Compression::Rle => reconstruct(&rle_decompress(data)),
+        Compression::Zips | Compression::Zip => reconstruct(&decompress_to_vec_zlib(data).unwrap()),
+    }
+}
+
+/// Converts a normal or zero half precision float
+fn half_to_f64(bits: u16) -> f64 {
+    if bits & 0x7fff == 0 {
+        return 0.0;
+    }
+    let exponent = ((bits >> 10) & 0x1f) as i32 - 15;
+    let mantissa = 1.0 + (bits & 0x3ff) as f64 / 1024.0;
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    sign * mantissa * 2f64.powi(exponent)
+}
+
+/// Decodes every block of a half float file into one row of values per channel and scanline
+fn decode_halves(compression: Compression, channels: usize, lines_per_block: usize) -> Vec<f64> {
+    let file = encode_with(compression);
+    let block_count = HEIGHT.div_ceil(lines_per_block);
+    let (_, blocks) = parse(&file, block_count);
+
+    let mut values = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        assert_eq!(block.first_line as usize, i * lines_per_block);
+        let lines = lines_per_block.min(HEIGHT - i * lines_per_block);
+        let raw = decompress(compression, &block.data, lines * channels * WIDTH * 2);
+        assert_eq!(raw.len(), lines * channels * WIDTH * 2);
+        values.extend(
+            raw.chunks_exact(2)
+                .map(|b| half_to_f64(u16::from_le_bytes([b[0], b[1]]))),
+        );
+    }
+    values
+}
+
+/// The values of every scanline, one channel after another in the order B, G, R
+fn expected_halves() -> Vec<f64> {
+    let image = image();
+    let mut values = Vec::new();
+    for y in 0..HEIGHT {
+        for axis in [2, 1, 0] {
+            values.extend((0..WIDTH).map(|x| image.get_pixel(x, y)[axis]));
+        }
+    }
+    values
+}
+
+#[test]
+fn header_describes_the_image() {
+    let file = encode_with(Compression::None);
+    let (attributes, _) = parse(&file, HEIGHT);
+
+    let names: Vec<&str> = attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "channels",
+            "compression",
+            "dataWindow",
+            "displayWindow",
+            "lineOrder",
+            "pixelAspectRatio",
+            "screenWindowCenter",
+            "screenWindowWidth",
+        ]
+    );
+
+    let channels = attribute(&attributes, "channels");
+    assert_eq!(channels.kind, "chlist");
+    // Sorted by name and stored as halves
+    assert_eq!(channels.value, chlist(&["B", "G", "R"], 1));
+
+    let window: Vec<u8> = [0i32, 0, WIDTH as i32 - 1, HEIGHT as i32 - 1]
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect();
+    let data_window = attribute(&attributes, "dataWindow");
+    assert_eq!(data_window.kind, "box2i");
+    assert_eq!(data_window.value, window);
+    assert_eq!(attribute(&attributes, "displayWindow").value, window);
+    assert_eq!(attribute(&attributes, "lineOrder").value, [0]);
+}
+
+#[test]
+fn compression_is_recorded() {
+    for (compression, id) in [
+        (Compression::None, 0),
+        (Compression::Rle, 1),
+        (Compression::Zips, 2),
+        (Compression::Zip, 3),
+    ] {
+        let lines_per_block = if compression == Compression::Zip {
+            16
+        } else {
+            1
+        };
+        let file = encode_with(compression);
+        let (attributes, _) = parse(&file, HEIGHT.div_ceil(lines_per_block));
+        let attribute = attribute(&attributes, "compression");
+        assert_eq!(attribute.kind, "compression");
+        assert_eq!(attribute.value, [id], "{:?}", compression);
+    }
+}
+
+#[test]
+fn uncompressed_scanlines_round_trip() {
+    assert_eq!(decode_halves(Compression::None, 3, 1), expected_halves());
+}
+
+#[test]
+fn rle_scanlines_round_trip() {
+    let file = encode_with(Compression::Rle);
+    let (_, blocks) = parse(&file, HEIGHT);
+    // The flat image gets smaller
+    assert!(blocks.iter().all(|b| b.data.len() < 3 * WIDTH * 2));
+    assert_eq!(decode_halves(Compression::Rle, 3, 1), expected_halves());
+}
+
+#[test]
+fn zip_blocks_round_trip() {
+    // 16 scanlines per block, so the second block holds the last 4
+    let file = encode_with(Compression::Zip);
+    let (_, blocks) = parse(&file, 2);
+    assert_eq!(blocks[1].first_line, 16);
+    assert!(blocks.iter().all(|b| b.data.len() < 16 * 3 * WIDTH * 2));
+    assert_eq!(decode_halves(Compression::Zip, 3, 16), expected_halves());
+}
+
+#[test]
+fn extra_channel_is_stored_by_name() {
+    let depth: Vec<f64> = (0..WIDTH * HEIGHT).map(|i| i as f64 + 0.5).collect();
+    let buffer = SharedBuffer::default();
+    let mut renderer = EXR::from_writer(buffer.clone());
+    renderer.set_compression(Compression::None);
+    renderer.set_pixel_type(PixelType::Float);
+    renderer.set_alpha(true);
+    renderer.add_channel("Z", depth.clone());
+    let file = encode(renderer, &buffer);
+
+    let (attributes, blocks) = parse(&file, HEIGHT);
+    assert_eq!(
+        attribute(&attributes, "channels").value,
+        chlist(&["A", "B", "G", "R", "Z"], 2)
+    );
+
+    let image = image();
+    for (y, block) in blocks.iter().enumerate() {
+        let samples: Vec<f32> = block
+            .data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(samples.len(), 5 * WIDTH);
+        let channel = |i: usize| &samples[i * WIDTH..(i + 1) * WIDTH];
+        assert!(channel(0).iter().all(|&a| a == 1.0));
+        for x in 0..WIDTH {
+            let pixel = image.get_pixel(x, y);
+            assert_eq!(channel(1)[x] as f64, pixel.z);
+            assert_eq!(channel(2)[x] as f64, pixel.y);
+            assert_eq!(channel(3)[x] as f64, pixel.x);
+            assert_eq!(channel(4)[x] as f64, depth[y * WIDTH + x]);
+        }
+    }
+}
+
+#[test]
+fn invalid_channels_are_rejected() {
+    let mut renderer = EXR::from_writer(SharedBuffer::default());
+    renderer.add_channel("Z", vec![0.0; 3]);
+    let error = renderer.draw(&image()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+    let mut renderer = EXR::from_writer(SharedBuffer::default());
+    renderer.add_channel("R", vec![0.0; WIDTH * HEIGHT]);
+    let error = renderer.draw(&image()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn invalid_channel_names_are_rejected() {
+    let long = "N".repeat(32);
+    for name in ["", "Z\0", long.as_str()] {
+        let mut renderer = EXR::from_writer(SharedBuffer::default());
+        renderer.add_channel(name, vec![0.0; WIDTH * HEIGHT]);
+        let error = renderer.draw(&image()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{:?}", name);
+    }
+    // The longest name that needs no flag
+    let mut renderer = EXR::from_writer(SharedBuffer::default());
+    renderer.add_channel(&long[1..], vec![0.0; WIDTH * HEIGHT]);
+    assert!(renderer.draw(&image()).is_ok());
+}
+
+#[test]
+fn empty_image_is_rejected() {
+    for (width, height) in [(0, 0), (4, 0), (0, 4)] {
+        let mut renderer = EXR::from_writer(SharedBuffer::default());
+        let error = renderer.draw(&Image::new(width, height)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
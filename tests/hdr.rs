@@ -0,0 +1,197 @@
+use zharko::{
+    loaders::{parse_hdr, LoadError},
+    math::Vec3,
+    renderers::{Image, Renderer, HDR},
+};
+
+mod common;
+
+use common::{assert_format_error, SharedBuffer};
+
+fn header(width: usize, height: usize) -> Vec<u8> {
+    format!(
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        height, width
+    )
+    .into_bytes()
+}
+
+fn assert_pixels(image: &Image, expected: &[Vec3]) {
+    assert_eq!(image.pixels().len(), expected.len());
+    for (actual, expected) in image.pixels().iter().zip(expected) {
+        assert!(
+            (*actual - *expected).length() < 1e-12,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn flat_scanlines_with_repeats() {
+    let mut data = header(3, 2);
+    // An exponent of 129 scales the mantissas by 1/128
+    data.extend([128, 64, 32, 129]);
+    // Repeat the previous pixel twice
+    data.extend([1, 1, 1, 2]);
+    data.extend([0, 0, 0, 0]);
+    data.extend([64, 128, 255, 130]);
+    data.extend([1, 2, 3, 0]);
+
+    let image = parse_hdr(&data).unwrap();
+    assert_eq!((image.width, image.height), (3, 2));
+    let a = Vec3::new(1.0, 0.5, 0.25);
+    assert_pixels(
+        &image,
+        &[
+            a,
+            a,
+            a,
+            Vec3::zero(),
+            Vec3::new(1.0, 2.0, 255.0 / 64.0),
+            Vec3::zero(),
+        ],
+    );
+}
+
+#[test]
+fn run_length_encoded_scanline() {
+    let mut data = header(8, 1);
+    data.extend([2, 2, 0, 8]);
+    // Red: a run of 8
+    data.extend([128 + 8, 128]);
+    // Green: 8 literal values
+    data.extend([8, 0, 16, 32, 48, 64, 80, 96, 112]);
+    // Blue: two runs of 4
+    data.extend([128 + 4, 0, 128 + 4, 64]);
+    // Exponent: 3 literals followed by a run of 5
+    data.extend([3, 129, 129, 129, 128 + 5, 129]);
+
+    let image = parse_hdr(&data).unwrap();
+    let expected: Vec<Vec3> = (0..8)
+        .map(|x| {
+            let blue = if x < 4 { 0.0 } else { 0.5 };
+            Vec3::new(1.0, x as f64 * 16.0 / 128.0, blue)
+        })
+        .collect();
+    assert_pixels(&image, &expected);
+}
+
+#[test]
+fn zero_size_is_rejected() {
+    assert_format_error(parse_hdr(&header(0, 0)));
+    assert_format_error(parse_hdr(&header(4, 0)));
+}
+
+#[test]
+fn huge_size_is_rejected() {
+    assert_format_error(parse_hdr(&header(usize::MAX / 2, 4)));
+    assert_format_error(parse_hdr(&header(1 << 20, 1 << 20)));
+}
+
+#[test]
+fn invalid_run_length_is_rejected() {
+    let mut data = header(8, 1);
+    data.extend([2, 2, 0, 8]);
+    // A run of 9 overflows the scanline
+    data.extend([128 + 9, 128]);
+    assert_format_error(parse_hdr(&data));
+}
+
+#[test]
+fn truncated_pixels_are_rejected() {
+    let mut data = header(2, 2);
+    data.extend([128, 128, 128, 128]);
+    assert_format_error(parse_hdr(&data));
+}
+
+#[test]
+fn missing_signature_is_rejected() {
+    match parse_hdr(b"P6\n") {
+        Err(LoadError::Parse { line, .. }) => assert_eq!(line, 1),
+        _ => panic!("expected a parse error"),
+    }
+}
+
+/// An image over a wide range of brightness with flat runs in every row
+fn radiance(width: usize, height: usize) -> Image {
+    let mut image = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = if x % 4 == 3 {
+                Vec3::new(1000.0, 0.001, 0.0)
+            } else {
+                Vec3::new(0.1 * x as f64, 0.3 + y as f64, 1.0 / (x + y + 1) as f64)
+            };
+            image.set_pixel(x, y, pixel);
+        }
+    }
+    image
+}
+
+fn round_trip(image: &Image, run_length_encoding: bool) -> (Vec<u8>, Image) {
+    let buffer = SharedBuffer::default();
+    let mut renderer = HDR::from_writer(buffer.clone());
+    renderer.set_run_length_encoding(run_length_encoding);
+    renderer.draw(image).unwrap();
+    let data = buffer.contents();
+    let decoded = parse_hdr(&data).unwrap();
+    (data, decoded)
+}
+
+/// The shared exponent keeps 8 bits of the brightest channel, so every channel is off by less
+/// than 1/128 of it
+fn assert_close(decoded: &Image, image: &Image) {
+    assert_eq!((decoded.width, decoded.height), (image.width, image.height));
+    for (actual, expected) in decoded.pixels().iter().zip(image.pixels()) {
+        let tolerance = expected.x.max(expected.y).max(expected.z) / 128.0;
+        for axis in 0..3 {
+            assert!(
+                (actual[axis] - expected[axis]).abs() <= tolerance,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn run_length_encoded_round_trip() {
+    let image = radiance(16, 3);
+    let (data, decoded) = round_trip(&image, true);
+    assert_close(&decoded, &image);
+    // The pixels start with the marker of a new-style RLE scanline
+    let header = b"-Y 3 +X 16\n";
+    let pixels = data
+        .windows(header.len())
+        .position(|w| w == header)
+        .unwrap()
+        + header.len();
+    assert_eq!(data[pixels..pixels + 4], [2, 2, 0, 16]);
+}
+
+#[test]
+fn flat_round_trip() {
+    let image = radiance(16, 3);
+    let (_, decoded) = round_trip(&image, false);
+    assert_close(&decoded, &image);
+}
+
+#[test]
+fn narrow_images_are_stored_flat() {
+    // Readers don't recognize RLE scanlines narrower than 8 pixels
+    let image = radiance(5, 2);
+    let (data, decoded) = round_trip(&image, true);
+    assert_close(&decoded, &image);
+    let header_length = data.len() - 5 * 2 * 4;
+    assert!(data[..header_length].ends_with(b"-Y 2 +X 5\n"));
+}
+
+#[test]
+fn negative_values_are_written_as_zero() {
+    let image = Image::from_pixels(1, 1, vec![Vec3::new(-1.0, 0.5, -0.0)]);
+    let (_, decoded) = round_trip(&image, true);
+    assert_pixels(&decoded, &[Vec3::new(0.0, 0.5, 0.0)]);
+}
@@ -0,0 +1,128 @@
+use zharko::{
+    camera::{CameraBuilder, CameraError, LensElement, LensSystem, Projection},
+    loaders::{parse_lens, LoadError},
+};
+
+fn dgauss50() -> LensSystem {
+    LensSystem::load(concat!(env!("CARGO_MANIFEST_DIR"), "/lenses/dgauss50.txt")).unwrap()
+}
+
+/// Distance from the rear surface to the film
+fn film_distance(lens: &LensSystem) -> f64 {
+    lens.elements().last().unwrap().thickness
+}
+
+fn assert_parse_error(source: &str, expected_line: usize) {
+    match parse_lens(source) {
+        Err(LoadError::Parse { line, .. }) => assert_eq!(line, expected_line),
+        Err(e) => panic!("expected a parse error, got {}", e),
+        Ok(_) => panic!("expected a parse error on line {}", expected_line),
+    }
+}
+
+#[test]
+fn prescription_is_loaded() {
+    let lens = dgauss50();
+    assert_eq!(lens.elements().len(), 11);
+    // The aperture stop
+    assert_eq!(
+        lens.elements()[5],
+        LensElement {
+            radius: 0.0,
+            thickness: 4.5,
+            ior: 0.0,
+            aperture: 17.1,
+        }
+    );
+}
+
+#[test]
+fn focal_length_matches_the_design() {
+    let focal_length = dgauss50().focal_length().unwrap();
+    assert!((focal_length - 50.0).abs() < 1.0, "{}", focal_length);
+
+    // Scaling the lens scales the focal length
+    let scaled = dgauss50().scaled(0.001).focal_length().unwrap();
+    assert!((scaled - 0.001 * focal_length).abs() < 1e-9, "{}", scaled);
+}
+
+#[test]
+fn closer_focus_moves_the_film_back() {
+    let lens = dgauss50();
+    let distances = [1e6, 10_000.0, 1_000.0, 500.0, 200.0];
+    let film: Vec<f64> = distances
+        .iter()
+        .map(|&d| film_distance(&lens.focus(d).unwrap()))
+        .collect();
+    assert!(film.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", film);
+    // Everything else stays in place
+    let focused = lens.focus(1_000.0).unwrap();
+    let count = lens.elements().len();
+    assert_eq!(
+        focused.elements()[..count - 1],
+        lens.elements()[..count - 1]
+    );
+}
+
+#[test]
+fn focus_inside_the_focal_length_is_impossible() {
+    // Points closer than the front focal point, which lies less than a focal length in front of
+    // the lens, have no real image
+    let lens = dgauss50();
+    for distance in [25.0, 10.0] {
+        assert!(lens.focus(distance).is_none(), "{}", distance);
+    }
+    let camera = CameraBuilder::new().lens(lens, 43.3).focus_dist(10.0);
+    assert_eq!(
+        camera.build().err(),
+        Some(CameraError::LensCannotFocus(10.0))
+    );
+}
+
+#[test]
+fn lens_cameras_are_validated() {
+    let camera = CameraBuilder::new().focus_dist(1_000.0);
+    assert!(camera.clone().lens(dgauss50(), 43.3).build().is_ok());
+    assert_eq!(
+        camera.clone().lens(dgauss50(), 0.0).build().err(),
+        Some(CameraError::InvalidFilmDiagonal(0.0))
+    );
+    let orthographic = Projection::Orthographic { view_height: 1.0 };
+    assert_eq!(
+        camera
+            .lens(dgauss50(), 43.3)
+            .projection(orthographic)
+            .build()
+            .err(),
+        Some(CameraError::UnsupportedLensProjection(orthographic))
+    );
+}
+
+#[test]
+fn empty_lens_system_is_rejected() {
+    assert!(LensSystem::new(Vec::new()).is_none());
+    match parse_lens("# only a comment\n\n") {
+        Err(LoadError::Format(_)) => (),
+        _ => panic!("expected a format error"),
+    }
+}
+
+#[test]
+fn invalid_surfaces_report_their_line() {
+    let valid = "# radius thickness ior aperture\n29.475 3.76 1.67 25.2\n";
+    assert!(parse_lens(valid).is_ok());
+
+    // Indices of refraction below 1, except 0 for air
+    assert_parse_error(&format!("{}84.83 0.12 0.5 25.2\n", valid), 3);
+    assert_parse_error(&format!("{}84.83 0.12 -1 25.2\n", valid), 3);
+    // Apertures must be positive and fit on the surface
+    assert_parse_error(&format!("{}\n84.83 0.12 1 0\n", valid), 4);
+    assert_parse_error(&format!("{}84.83 0.12 1 -3\n", valid), 3);
+    assert_parse_error(&format!("{}10 0.12 1 25.2\n", valid), 3);
+    // Four values per line
+    assert_parse_error("29.475 3.76 1.67\n", 1);
+    assert_parse_error(&format!("{}84.83 0.12 1 25.2 7\n", valid), 3);
+    // Numbers only, and no negative thickness
+    assert_parse_error(&format!("{}84.83 x 1 25.2\n", valid), 3);
+    assert_parse_error(&format!("{}84.83 -0.12 1 25.2\n", valid), 3);
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use zharko::{
+    camera::CameraBuilder,
+    math::{
+        backgrounds::SolidBackground,
+        hittables::{HittableList, Sphere},
+        interval::Interval,
+        materials::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
+        textures::UvChecker,
+        HitRecord, HitResult, Hittable, Ray, Vec3,
+    },
+};
+
+mod common;
+
+use common::assert_near;
+
+const RADIANCE: Vec3 = Vec3 {
+    x: 4.0,
+    y: 2.0,
+    z: 0.5,
+};
+
+/// The hit of a ray shot from the positive `z` axis at `target` on a unit sphere at the origin
+fn hit(mat: Arc<dyn Material>, target: Vec3) -> (Ray, HitRecord) {
+    let sphere = Sphere::new(Vec3::zero(), 1.0, mat);
+    let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), target - Vec3::new(0.0, 0.0, 5.0));
+    match sphere.hit(&ray, Interval::new(0.0001, f64::INFINITY)) {
+        HitResult::Hit(rec) => (ray, rec),
+        HitResult::NoHit => panic!("expected a hit"),
+    }
+}
+
+/// A camera looking at a sphere that fills the whole view, in an otherwise black world
+fn render(mat: Arc<dyn Material>) -> Vec<Vec3> {
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(Vec3::new(0.0, 0.0, -3.0), 2.0, mat)));
+    let camera = CameraBuilder::new()
+        .resolution(4, 4)
+        .vfov(20.0)
+        .samples_per_pixel(4)
+        .threads(1)
+        .background(SolidBackground::new(Vec3::zero()))
+        .build()
+        .unwrap();
+    camera.render(&world).pixels().to_vec()
+}
+
+#[test]
+fn diffuse_light_emits_its_radiance() {
+    let (ray, rec) = hit(Arc::new(DiffuseLight::new(RADIANCE)), Vec3::zero());
+    assert_near(rec.mat.emitted(&ray, &rec), RADIANCE);
+    // Lights absorb everything that reaches them
+    assert!(rec.mat.scatter(&ray, &rec).is_none());
+}
+
+#[test]
+fn textured_light_emits_the_texture() {
+    let dark = Vec3::new(0.1, 0.1, 0.1);
+    let checker = UvChecker::from_colors(4.0, 2.0, RADIANCE, dark);
+    let light: Arc<dyn Material> = Arc::new(DiffuseLight::from_texture(Arc::new(checker)));
+    // Seen from the positive `z` axis, `u` is 0.25 in the center, which is the border between
+    // the first and second column of squares. Both points are in the upper row.
+    let (ray, rec) = hit(light.clone(), Vec3::new(-0.2, 0.2, 0.0));
+    assert!(rec.u < 0.25 && rec.v > 0.5);
+    assert_near(rec.mat.emitted(&ray, &rec), dark);
+    let (ray, rec) = hit(light, Vec3::new(0.2, 0.2, 0.0));
+    assert!(rec.u > 0.25 && rec.v > 0.5);
+    assert_near(rec.mat.emitted(&ray, &rec), RADIANCE);
+}
+
+#[test]
+fn other_materials_are_not_lit() {
+    let materials: [Arc<dyn Material>; 3] = [
+        Arc::new(Lambertian::new(Vec3::new(0.9, 0.9, 0.9))),
+        Arc::new(Metal::new(Vec3::new(0.9, 0.9, 0.9), 0.1)),
+        Arc::new(Dielectric::new(1.5)),
+    ];
+    for mat in materials {
+        let (ray, rec) = hit(mat, Vec3::zero());
+        assert_near(rec.mat.emitted(&ray, &rec), Vec3::zero());
+    }
+}
+
+#[test]
+fn lights_are_visible_in_a_dark_world() {
+    for pixel in render(Arc::new(DiffuseLight::new(RADIANCE))) {
+        assert_near(pixel, RADIANCE);
+    }
+    // Without a light nothing is reflected
+    for pixel in render(Arc::new(Lambertian::new(Vec3::new(0.9, 0.9, 0.9)))) {
+        assert_near(pixel, Vec3::zero());
+    }
+}
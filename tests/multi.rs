@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+
+use zharko::{
+    loaders::parse_ppm,
+    math::Vec3,
+    renderers::{Image, MultiRenderer, Renderer, PFM, PPM},
+};
+
+mod common;
+
+use common::SharedBuffer;
+
+/// A writer that fails every write with an error of the given kind
+struct FailingWriter(io::ErrorKind);
+
+impl Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(self.0, "cannot write"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::new(self.0, "cannot write"))
+    }
+}
+
+fn image() -> Image {
+    Image::from_pixels(
+        2,
+        1,
+        vec![Vec3::new(0.25, 0.5, 1.0), Vec3::new(1.0, 0.0, 0.5)],
+    )
+}
+
+#[test]
+fn every_renderer_draws_the_image() {
+    let (ppm, pfm) = (SharedBuffer::default(), SharedBuffer::default());
+    let mut renderer = MultiRenderer::new()
+        .with(PPM::from_writer(ppm.clone()))
+        .with(PFM::from_writer(pfm.clone()));
+    renderer.draw(&image()).unwrap();
+    assert_eq!(parse_ppm(&ppm.contents()).unwrap().width, 2);
+    assert!(pfm.contents().starts_with(b"PF\n2 1\n"));
+}
+
+#[test]
+fn later_renderers_draw_after_a_failure() {
+    let buffer = SharedBuffer::default();
+    let mut renderer = MultiRenderer::new()
+        .with(PPM::from_writer(FailingWriter(
+            io::ErrorKind::PermissionDenied,
+        )))
+        .with(PPM::from_writer(buffer.clone()))
+        .with(PFM::from_writer(FailingWriter(io::ErrorKind::BrokenPipe)));
+
+    let error = renderer.draw(&image()).unwrap_err();
+    // The first error wins
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    let written = parse_ppm(&buffer.contents()).unwrap();
+    assert_eq!((written.width, written.height), (2, 1));
+}
+
+#[test]
+fn empty_renderer_does_nothing() {
+    assert!(MultiRenderer::new().draw(&image()).is_ok());
+}
@@ -0,0 +1,96 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use zharko::math::{
+    textures::noise::{Interpolation, Perlin, Worley},
+    Vec3,
+};
+
+/// Random points spread over many lattice cells, on both sides of zero
+fn points() -> Vec<Vec3> {
+    let mut rng = StdRng::seed_from_u64(11);
+    (0..2000)
+        .map(|_| {
+            Vec3::new(
+                rng.random_range(-40.0..40.0),
+                rng.random_range(-40.0..40.0),
+                rng.random_range(-40.0..40.0),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn perlin_noise_depends_only_on_the_seed() {
+    let (a, b, other) = (Perlin::new(5), Perlin::new(5), Perlin::new(6));
+    let points = points();
+    for p in &points {
+        assert_eq!(a.noise(p), b.noise(p));
+        assert_eq!(a.turbulence(p, 7), b.turbulence(p, 7));
+    }
+    assert!(points.iter().any(|p| a.noise(p) != other.noise(p)));
+}
+
+#[test]
+fn perlin_noise_stays_in_range() {
+    for interpolation in [Interpolation::Trilinear, Interpolation::Hermite] {
+        let mut perlin = Perlin::new(5);
+        perlin.set_interpolation(interpolation);
+        let values: Vec<f64> = points().iter().map(|p| perlin.noise(p)).collect();
+        assert!(values.iter().all(|n| n.abs() <= 1.0), "{:?}", interpolation);
+        // Not a constant either
+        assert!(values.iter().any(|&n| n > 0.1) && values.iter().any(|&n| n < -0.1));
+    }
+}
+
+#[test]
+fn perlin_noise_is_zero_on_the_lattice() {
+    let perlin = Perlin::new(5);
+    for p in [
+        Vec3::zero(),
+        Vec3::new(3.0, -7.0, 12.0),
+        Vec3::new(-300.0, 0.0, 255.0),
+    ] {
+        assert_eq!(perlin.noise(&p), 0.0, "{:?}", p);
+    }
+}
+
+#[test]
+fn turbulence_is_not_negative() {
+    let perlin = Perlin::new(5);
+    for p in points() {
+        assert!(perlin.turbulence(&p, 7) >= 0.0, "{:?}", p);
+    }
+    assert_eq!(perlin.turbulence(&Vec3::new(0.3, 0.4, 0.5), 0), 0.0);
+}
+
+#[test]
+fn worley_noise_depends_only_on_the_seed() {
+    let (a, b, other) = (Worley::new(5), Worley::new(5), Worley::new(6));
+    let points = points();
+    for p in &points {
+        assert_eq!(a.distances(p), b.distances(p));
+    }
+    assert!(points.iter().any(|p| a.distances(p) != other.distances(p)));
+}
+
+#[test]
+fn worley_distances_are_ordered() {
+    let worley = Worley::new(5);
+    for p in points() {
+        let (f1, f2, _) = worley.distances(&p);
+        // The feature point of the cell containing `p` is at most a cell diagonal away
+        assert!((0.0..=3f64.sqrt()).contains(&f1), "{:?}: {}", p, f1);
+        assert!(f1 <= f2 && f2.is_finite(), "{:?}: {} {}", p, f1, f2);
+    }
+}
+
+#[test]
+fn worley_distance_is_continuous() {
+    // Moving `p` changes the distance to the closest feature point by at most as much
+    let worley = Worley::new(5);
+    let step = Vec3::new(1e-3, -2e-3, 2e-3);
+    for p in points() {
+        let (f1, _, _) = worley.distances(&p);
+        let (moved, _, _) = worley.distances(&(p + step));
+        assert!((f1 - moved).abs() <= step.length() + 1e-12, "{:?}", p);
+    }
+}
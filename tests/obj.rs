@@ -0,0 +1,199 @@
+use std::{path::Path, sync::Arc};
+
+use zharko::{
+    loaders::{parse_mtl, parse_obj, LoadError},
+    math::{
+        hittables::HittableList, interval::Interval, materials::Material, HitRecord, HitResult,
+        Hittable, Ray, Vec3,
+    },
+};
+
+mod common;
+
+use common::assert_near;
+
+fn parse(source: &str) -> HittableList {
+    parse_obj(source, Path::new("")).expect("valid OBJ")
+}
+
+/// The first hit of a ray shot down the `z` axis through `(x, y)`
+fn hit_from_above(world: &HittableList, x: f64, y: f64) -> Option<HitRecord> {
+    let ray = Ray::new(Vec3::new(x, y, 5.0), Vec3::new(0.0, 0.0, -1.0));
+    match world.hit(&ray, Interval::new(0.0001, f64::INFINITY)) {
+        HitResult::Hit(rec) => Some(rec),
+        HitResult::NoHit => None,
+    }
+}
+
+fn assert_parse_error(result: Result<HittableList, LoadError>, expected_line: usize) {
+    match result {
+        Err(LoadError::Parse { line, .. }) => assert_eq!(line, expected_line),
+        Err(e) => panic!("expected a parse error, got {}", e),
+        Ok(_) => panic!("expected a parse error on line {}", expected_line),
+    }
+}
+
+#[test]
+fn quad_is_triangulated() {
+    let world = parse(
+        "v 0 0 0\n\
+         v 1 0 0\n\
+         v 1 1 0\n\
+         v 0 1 0\n\
+         f 1 2 3 4\n",
+    );
+    for (x, y) in [(0.1, 0.1), (0.9, 0.1), (0.9, 0.9), (0.1, 0.9), (0.5, 0.5)] {
+        assert!(hit_from_above(&world, x, y).is_some(), "({}, {})", x, y);
+    }
+    assert!(hit_from_above(&world, 1.1, 0.5).is_none());
+}
+
+#[test]
+fn concave_polygon_keeps_its_notch() {
+    // A "W" shaped polygon whose first vertex sees the notch, so a triangle fan from it would
+    // cover the notch
+    let world = parse(
+        "v 4 4 0\n\
+         v 2 1 0\n\
+         v 0 4 0\n\
+         v 0 0 0\n\
+         v 4 0 0\n\
+         f 1 2 3 4 5\n",
+    );
+    assert!(hit_from_above(&world, 2.0, 3.0).is_none());
+    for (x, y) in [(0.3, 3.0), (3.7, 3.0), (2.0, 0.5), (0.5, 0.5), (3.5, 0.5)] {
+        assert!(hit_from_above(&world, x, y).is_some(), "({}, {})", x, y);
+    }
+}
+
+#[test]
+fn negative_indices_count_back() {
+    let world = parse(
+        "v 10 10 0\n\
+         v 0 0 0\n\
+         v 1 0 0\n\
+         v 0 1 0\n\
+         f -3 -2 -1\n",
+    );
+    assert!(hit_from_above(&world, 0.2, 0.2).is_some());
+    assert!(hit_from_above(&world, 5.0, 5.0).is_none());
+}
+
+#[test]
+fn vertex_normals_are_interpolated() {
+    let world = parse(
+        "v 0 0 0\n\
+         v 1 0 0\n\
+         v 0 1 0\n\
+         vn 0 1 1\n\
+         f 1//1 2//1 3//1\n",
+    );
+    let rec = hit_from_above(&world, 0.25, 0.25).unwrap();
+    assert_near(rec.normal, Vec3::new(0.0, 1.0, 1.0).unit());
+}
+
+#[test]
+fn texture_coordinates_and_normals_are_interpolated() {
+    let world = parse(
+        "v 0 0 0\n\
+         v 1 0 0\n\
+         v 0 1 0\n\
+         vt 0 0\n\
+         vt 0.5 0\n\
+         vt 0 0.5\n\
+         vn 0 0 2\n\
+         f 1/1/1 2/2/1 3/3/1\n",
+    );
+    let rec = hit_from_above(&world, 0.5, 0.25).unwrap();
+    assert!((rec.u - 0.25).abs() < 1e-9);
+    assert!((rec.v - 0.125).abs() < 1e-9);
+    assert_near(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn bad_face_reports_its_line() {
+    let source = "v 0 0 0\n\
+                  v 1 0 0\n\
+                  # comment\n\
+                  v 0 1 0\n\
+                  f 1 2 4\n";
+    assert_parse_error(parse_obj(source, Path::new("")), 5);
+    assert_parse_error(parse_obj("v 0 0 0\nv 1 0 0\nf 1 2\n", Path::new("")), 3);
+    assert_parse_error(parse_obj("v 0 0 0\n\nf 1 0 1\n", Path::new("")), 3);
+    assert_parse_error(parse_obj("v 0 0 0\nf 1 x 1\n", Path::new("")), 2);
+}
+
+#[test]
+fn zero_normal_is_rejected() {
+    let source = "v 0 0 0\n\
+                  v 1 0 0\n\
+                  v 0 1 0\n\
+                  vn 0 0 0\n\
+                  f 1//1 2//1 3//1\n";
+    assert_parse_error(parse_obj(source, Path::new("")), 4);
+}
+
+#[test]
+fn unknown_material_is_rejected() {
+    assert_parse_error(parse_obj("v 0 0 0\nusemtl missing\n", Path::new("")), 2);
+}
+
+/// Scatters a ray that comes straight down onto a surface facing up
+fn scatter(material: &Arc<dyn Material>) -> (Vec3, Vec3) {
+    let ray = Ray::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    let rec = HitRecord {
+        point: Vec3::zero(),
+        normal: Vec3::new(0.0, 1.0, 0.0),
+        t: 1.0,
+        front_face: true,
+        u: 0.0,
+        v: 0.0,
+        dpdu: Vec3::new(1.0, 0.0, 0.0),
+        dpdv: Vec3::new(0.0, 0.0, 1.0),
+        mat: material.clone(),
+    };
+    let result = material.scatter(&ray, &rec).expect("the ray scatters");
+    (result.attenuation, result.scattered.dir)
+}
+
+#[test]
+fn mtl_materials_are_mapped() {
+    let materials = parse_mtl(
+        "newmtl diffuse\n\
+         Kd 0.2 0.4 0.6\n\
+         \n\
+         newmtl mirror\n\
+         Kd 0.1 0.1 0.1\n\
+         Ks 0.9 0.8 0.7\n\
+         Ns 100000000\n\
+         \n\
+         newmtl air\n\
+         Kd 0.5 0.5 0.5\n\
+         Ni 1.0\n\
+         d 0.5\n",
+    )
+    .unwrap();
+    assert_eq!(materials.len(), 3);
+
+    // Lambertian reflects the diffuse color
+    let (attenuation, _) = scatter(&materials["diffuse"]);
+    assert_near(attenuation, Vec3::new(0.2, 0.4, 0.6));
+
+    // A high exponent makes a sharp metal with the specular color
+    let (attenuation, dir) = scatter(&materials["mirror"]);
+    assert_near(attenuation, Vec3::new(0.9, 0.8, 0.7));
+    assert!((dir.unit() - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-3);
+
+    // A dielectric with the index of air lets the ray straight through
+    let (attenuation, dir) = scatter(&materials["air"]);
+    assert_near(attenuation, Vec3::new(1.0, 1.0, 1.0));
+    assert_near(dir.unit(), Vec3::new(0.0, -1.0, 0.0));
+}
+
+#[test]
+fn mtl_statement_before_newmtl_is_rejected() {
+    match parse_mtl("# library\nKd 1 1 1\n") {
+        Err(LoadError::Parse { line, .. }) => assert_eq!(line, 2),
+        _ => panic!("expected a parse error"),
+    }
+}
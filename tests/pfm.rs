@@ -0,0 +1,98 @@
+use zharko::{
+    loaders::parse_pfm,
+    math::Vec3,
+    renderers::{Image, Renderer, PFM},
+};
+
+mod common;
+
+use common::{assert_format_error, SharedBuffer};
+
+/// An image over a wide range of brightness where every pixel differs
+fn radiance() -> Image {
+    let mut image = Image::new(4, 3);
+    for y in 0..3 {
+        for x in 0..4 {
+            let pixel = Vec3::new(0.1 * x as f64, 1000.0 * y as f64, -1.0 / (x + y + 1) as f64);
+            image.set_pixel(x, y, pixel);
+        }
+    }
+    image
+}
+
+fn encode(grayscale: bool, image: &Image) -> Vec<u8> {
+    let buffer = SharedBuffer::default();
+    let mut renderer = PFM::from_writer(buffer.clone());
+    renderer.set_grayscale(grayscale);
+    renderer.draw(image).unwrap();
+    buffer.contents()
+}
+
+/// The samples are stored as 32-bit floats
+fn assert_close(actual: Vec3, expected: Vec3) {
+    for axis in 0..3 {
+        let tolerance = expected[axis].abs() * f32::EPSILON as f64;
+        assert!(
+            (actual[axis] - expected[axis]).abs() <= tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn rgb_round_trip() {
+    let image = radiance();
+    let data = encode(false, &image);
+    assert!(data.starts_with(b"PF\n4 3\n-1.0\n"));
+
+    let decoded = parse_pfm(&data).unwrap();
+    assert_eq!((decoded.width, decoded.height), (4, 3));
+    for (actual, expected) in decoded.pixels().iter().zip(image.pixels()) {
+        assert_close(*actual, *expected);
+    }
+}
+
+#[test]
+fn rows_are_stored_bottom_up() {
+    let data = encode(false, &radiance());
+    let first = &data[b"PF\n4 3\n-1.0\n".len()..][..12];
+    let samples: Vec<f32> = first
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    // The bottom left pixel
+    assert_eq!(samples, [0.0, 2000.0, -1.0 / 3.0]);
+}
+
+#[test]
+fn grayscale_round_trip() {
+    let image = radiance();
+    let data = encode(true, &image);
+    assert!(data.starts_with(b"Pf\n4 3\n-1.0\n"));
+
+    let decoded = parse_pfm(&data).unwrap();
+    for (actual, pixel) in decoded.pixels().iter().zip(image.pixels()) {
+        let luminance = 0.2126 * pixel.x + 0.7152 * pixel.y + 0.0722 * pixel.z;
+        assert_close(*actual, Vec3::new(luminance, luminance, luminance));
+    }
+}
+
+#[test]
+fn big_endian_samples_are_read() {
+    let mut data = b"PF\n1 1\n1.0\n".to_vec();
+    for sample in [0.5f32, 2.0, -4.0] {
+        data.extend_from_slice(&sample.to_be_bytes());
+    }
+    let image = parse_pfm(&data).unwrap();
+    assert_close(*image.get_pixel(0, 0), Vec3::new(0.5, 2.0, -4.0));
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    assert_format_error(parse_pfm(b"P6\n1 1\n-1.0\n"));
+    assert_format_error(parse_pfm(b"PF\n0 1\n-1.0\n"));
+    assert_format_error(parse_pfm(b"PF\n2 2\n-1.0\n\0\0\0\0"));
+    assert_format_error(parse_pfm(b"PF\n2 2\n"));
+}
@@ -0,0 +1,340 @@
+use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib};
+use zharko::{
+    loaders::parse_png,
+    math::{color::srgb_encode, Vec3},
+    renderers::{BitDepth, Color, Image, Renderer, PNG},
+};
+
+mod common;
+
+use common::{assert_format_error, SharedBuffer};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn chunk(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = (body.len() as u32).to_be_bytes().to_vec();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    // The decoder doesn't check the CRC
+    out.extend_from_slice(&[0; 4]);
+    out
+}
+
+/// Builds a PNG from already filtered scanlines
+fn build_png(width: u32, height: u32, color_type: u8, bit_depth: u8, scanlines: &[u8]) -> Vec<u8> {
+    let mut header = width.to_be_bytes().to_vec();
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+
+    let mut png = SIGNATURE.to_vec();
+    png.extend(chunk(b"IHDR", &header));
+    png.extend(chunk(b"IDAT", &compress_to_vec_zlib(scanlines, 6)));
+    png.extend(chunk(b"IEND", &[]));
+    png
+}
+
+/// Splits a PNG into its chunks
+fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    assert!(png.starts_with(&SIGNATURE));
+    let mut chunks = Vec::new();
+    let mut pos = SIGNATURE.len();
+    while pos < png.len() {
+        let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+        let kind = png[pos + 4..pos + 8].try_into().unwrap();
+        chunks.push((kind, png[pos + 8..pos + 8 + length].to_vec()));
+        pos += 12 + length;
+    }
+    chunks
+}
+
+fn bytes(pixel: Vec3) -> [u8; 3] {
+    let color = Color::from(pixel);
+    [color.r, color.g, color.b]
+}
+
+#[test]
+fn single_pixel() {
+    let png = build_png(1, 1, 2, 8, &[0, 255, 128, 0]);
+    let image = parse_png(&png).unwrap();
+    assert_eq!((image.width, image.height), (1, 1));
+    assert_eq!(bytes(*image.get_pixel(0, 0)), [255, 128, 0]);
+}
+
+#[test]
+fn zero_size_is_rejected() {
+    assert_format_error(parse_png(&build_png(0, 4, 2, 8, &[0; 4])));
+    assert_format_error(parse_png(&build_png(4, 0, 2, 8, &[])));
+}
+
+#[test]
+fn huge_size_is_rejected() {
+    assert_format_error(parse_png(&build_png(u32::MAX, u32::MAX, 2, 8, &[0; 4])));
+}
+
+#[test]
+fn truncated_image_data_is_rejected() {
+    assert_format_error(parse_png(&build_png(2, 2, 2, 8, &[0, 1, 2, 3])));
+}
+
+#[test]
+fn oversized_image_data_is_rejected() {
+    // A megabyte of zeros compresses to about a kilobyte, but a 2x2 gray image holds 6 bytes
+    let zeros = vec![0; 1 << 20];
+    assert_format_error(parse_png(&build_png(2, 2, 0, 8, &zeros)));
+    // Exactly the size of the image is fine
+    assert!(parse_png(&build_png(2, 2, 0, 8, &zeros[..6])).is_ok());
+}
+
+#[test]
+fn every_filter_type_is_decoded() {
+    // Two gray pixels per line with the bytes 10 and 30 over a line of 50 and 60, written with
+    // each of the filter types
+    let first = [0, 50, 60];
+    let filtered = [
+        [0, 10, 30],
+        [1, 10, 20],
+        [2, 10u8.wrapping_sub(50), 30u8.wrapping_sub(60)],
+        // The average of the left and the upper byte
+        [3, 10u8.wrapping_sub(25), 30u8.wrapping_sub(35)],
+        // Paeth picks the upper byte for the first pixel and the left one for the second
+        [4, 10u8.wrapping_sub(50), 20],
+    ];
+    for line in filtered {
+        let png = build_png(2, 2, 0, 8, &[first, line].concat());
+        let image = parse_png(&png).unwrap();
+        assert_eq!(bytes(*image.get_pixel(0, 1)), [10; 3], "filter {}", line[0]);
+        assert_eq!(bytes(*image.get_pixel(1, 1)), [30; 3], "filter {}", line[0]);
+    }
+}
+
+/// Linear values that the PNG renderer encodes as exactly these bytes
+fn gray_row(bytes: &[u8]) -> Vec<Vec3> {
+    bytes.iter().map(|&b| Color::new(b, b, b).into()).collect()
+}
+
+#[test]
+fn round_trip_uses_every_filter_type() {
+    const WIDTH: usize = 16;
+    let noise: Vec<u8> = (0..WIDTH).map(|i| (i * 73 + 11) as u8).collect();
+    let other_noise: Vec<u8> = (0..WIDTH).map(|i| (i * 151 + 97) as u8).collect();
+    // Every pixel after the first is the Paeth prediction from its neighbors, with the noise row
+    // above. The first one differs from the row above, so that it doesn't just repeat it.
+    let mut paeth = vec![0u8];
+    for i in 1..WIDTH {
+        let (a, b, c) = (
+            paeth[i - 1] as i16,
+            other_noise[i] as i16,
+            other_noise[i - 1] as i16,
+        );
+        let p = a + b - c;
+        let closest = if (p - a).abs() <= (p - b).abs() && (p - a).abs() <= (p - c).abs() {
+            a
+        } else if (p - b).abs() <= (p - c).abs() {
+            b
+        } else {
+            c
+        };
+        paeth.push(closest as u8);
+    }
+    // Every pixel is the average of its left and upper neighbor in a row of 200
+    let mut average = vec![100u8];
+    for i in 1..WIDTH {
+        average.push(((average[i - 1] as u16 + 200) / 2) as u8);
+    }
+
+    let rows: Vec<Vec<u8>> = vec![
+        noise.clone(),
+        // Repeats the line above
+        noise,
+        // Zeros need no filter
+        vec![0; WIDTH],
+        // A ramp predicted by its left neighbor
+        (0..WIDTH).map(|i| (i * 10) as u8).collect(),
+        vec![200; WIDTH],
+        average,
+        other_noise,
+        paeth,
+    ];
+    let pixels = rows.iter().flat_map(|row| gray_row(row)).collect();
+    let image = Image::from_pixels(WIDTH, rows.len(), pixels);
+
+    let buffer = SharedBuffer::default();
+    PNG::from_writer(buffer.clone()).draw(&image).unwrap();
+    let png = buffer.contents();
+
+    let decoded = parse_png(&png).unwrap();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &b) in row.iter().enumerate() {
+            assert_eq!(bytes(*decoded.get_pixel(x, y)), [b; 3], "({}, {})", x, y);
+        }
+    }
+
+    let idat = chunks(&png)
+        .into_iter()
+        .find(|(kind, _)| kind == b"IDAT")
+        .unwrap()
+        .1;
+    let raw = decompress_to_vec_zlib(&idat).unwrap();
+    let mut filters: Vec<u8> = raw.chunks_exact(WIDTH * 3 + 1).map(|l| l[0]).collect();
+    filters.sort();
+    filters.dedup();
+    assert_eq!(filters, [0, 1, 2, 3, 4]);
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn encode(renderer: impl FnOnce(SharedBuffer) -> PNG, image: &Image) -> Vec<u8> {
+    let buffer = SharedBuffer::default();
+    renderer(buffer.clone()).draw(image).unwrap();
+    buffer.contents()
+}
+
+/// A small image with a gradient in every channel
+fn gradient() -> Image {
+    let mut image = Image::new(5, 3);
+    for y in 0..3 {
+        for x in 0..5 {
+            image.set_pixel(
+                x,
+                y,
+                Vec3::new(x as f64 / 4.0, y as f64 / 2.0, (x + y) as f64 / 6.0),
+            );
+        }
+    }
+    image
+}
+
+#[test]
+fn chunks_have_valid_crcs() {
+    let png = encode(PNG::from_writer, &gradient());
+    let kinds: Vec<[u8; 4]> = chunks(&png).iter().map(|(kind, _)| *kind).collect();
+    assert_eq!(kinds, [*b"IHDR", *b"sRGB", *b"IDAT", *b"IEND"]);
+    // The perceptual rendering intent
+    assert_eq!(chunks(&png)[1].1, [0]);
+
+    let mut pos = SIGNATURE.len();
+    while pos < png.len() {
+        let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+        let crc_pos = pos + 8 + length;
+        let stored = u32::from_be_bytes(png[crc_pos..crc_pos + 4].try_into().unwrap());
+        // The CRC covers the chunk type and body
+        assert_eq!(stored, crc32(&png[pos + 4..crc_pos]));
+        pos = crc_pos + 4;
+    }
+    assert_eq!(pos, png.len());
+}
+
+#[test]
+fn header_describes_the_image() {
+    let png = encode(PNG::from_writer, &gradient());
+    let header = &chunks(&png)[0].1;
+    assert_eq!(header[..8], [0, 0, 0, 5, 0, 0, 0, 3]);
+    // 8 bits, RGB, deflate, adaptive filtering, no interlacing
+    assert_eq!(header[8..], [8, 2, 0, 0, 0]);
+}
+
+#[test]
+fn round_trip_eight_bit() {
+    let image = gradient();
+    let decoded = parse_png(&encode(PNG::from_writer, &image)).unwrap();
+    assert_eq!((decoded.width, decoded.height), (5, 3));
+    for (actual, expected) in decoded.pixels().iter().zip(image.pixels()) {
+        assert_eq!(bytes(*actual), bytes(*expected));
+    }
+}
+
+/// Reverses the filters of decompressed image data with `bpp` bytes per pixel
+fn unfilter(raw: &[u8], stride: usize, bpp: usize) -> Vec<Vec<u8>> {
+    let mut lines: Vec<Vec<u8>> = Vec::new();
+    for filtered in raw.chunks_exact(stride + 1) {
+        let above = lines.last().cloned().unwrap_or_else(|| vec![0; stride]);
+        let mut line = vec![0u8; stride];
+        for i in 0..stride {
+            let a = if i >= bpp { line[i - bpp] } else { 0 };
+            let b = above[i];
+            let c = if i >= bpp { above[i - bpp] } else { 0 };
+            let prediction = match filtered[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => {
+                    let p = a as i16 + b as i16 - c as i16;
+                    let (pa, pb, pc) = (
+                        (p - a as i16).abs(),
+                        (p - b as i16).abs(),
+                        (p - c as i16).abs(),
+                    );
+                    if pa <= pb && pa <= pc {
+                        a
+                    } else if pb <= pc {
+                        b
+                    } else {
+                        c
+                    }
+                }
+                filter => panic!("unknown filter {}", filter),
+            };
+            line[i] = filtered[i + 1].wrapping_add(prediction);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[test]
+fn round_trip_sixteen_bit_with_alpha() {
+    let image = gradient();
+    let png = encode(
+        |buffer| {
+            let mut renderer = PNG::from_writer(buffer);
+            renderer.set_bit_depth(BitDepth::Sixteen);
+            renderer.set_alpha(true);
+            renderer
+        },
+        &image,
+    );
+    let header = &chunks(&png)[0].1;
+    assert_eq!(header[8..10], [16, 6]);
+
+    // The decoder keeps the upper byte of every sample
+    let decoded = parse_png(&png).unwrap();
+    for (actual, expected) in decoded.pixels().iter().zip(image.pixels()) {
+        let (actual, expected) = (bytes(*actual), bytes(*expected));
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(a.abs_diff(e) <= 1, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    // The samples are the full 16-bit sRGB values, and every pixel is fully opaque
+    let idat = chunks(&png)
+        .into_iter()
+        .find(|(kind, _)| kind == b"IDAT")
+        .unwrap()
+        .1;
+    let lines = unfilter(&decompress_to_vec_zlib(&idat).unwrap(), 5 * 8, 8);
+    for (y, line) in lines.iter().enumerate() {
+        for (x, pixel) in line.chunks_exact(8).enumerate() {
+            let c = *image.get_pixel(x, y);
+            for (i, value) in [c.x, c.y, c.z].into_iter().enumerate() {
+                let expected = (srgb_encode(value).clamp(0.0, 1.0) * 65535.0).round() as u16;
+                let sample = u16::from_be_bytes([pixel[2 * i], pixel[2 * i + 1]]);
+                assert_eq!(sample, expected, "({}, {})", x, y);
+            }
+            assert_eq!(pixel[6..], [255, 255]);
+        }
+    }
+}
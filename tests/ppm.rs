@@ -0,0 +1,147 @@
+use zharko::{
+    loaders::parse_ppm,
+    math::Vec3,
+    renderers::{Color, Image, PpmFormat, Renderer, ToneMapper, ToneMapping, PPM},
+};
+
+mod common;
+
+use common::{assert_format_error, SharedBuffer};
+
+fn bytes(pixel: Vec3) -> [u8; 3] {
+    let color = Color::from(pixel);
+    [color.r, color.g, color.b]
+}
+
+fn assert_bytes(image: &Image, expected: &[[u8; 3]]) {
+    let actual: Vec<[u8; 3]> = image.pixels().iter().map(|&p| bytes(p)).collect();
+    assert_eq!(actual, expected);
+}
+
+/// A dim and a bright row, including radiance above the displayable range
+fn radiance() -> Image {
+    Image::from_pixels(
+        3,
+        2,
+        vec![
+            Vec3::new(0.0, 0.05, 0.1),
+            Vec3::new(0.2, 0.3, 0.4),
+            Vec3::new(0.5, 0.6, 0.7),
+            Vec3::new(0.8, 0.9, 1.0),
+            Vec3::new(1.5, 3.0, 0.25),
+            Vec3::new(10.0, 0.0, 1.0),
+        ],
+    )
+}
+
+/// Writes the image and decodes it again
+fn round_trip(format: PpmFormat, tone_mapper: ToneMapper, image: &Image) -> Image {
+    let buffer = SharedBuffer::default();
+    let mut renderer = PPM::from_writer(buffer.clone());
+    renderer.set_format(format);
+    renderer.set_tone_mapper(tone_mapper);
+    renderer.draw(image).unwrap();
+    parse_ppm(&buffer.contents()).unwrap()
+}
+
+#[test]
+fn ascii_image() {
+    let image =
+        parse_ppm(b"P3\n# a comment\n2 2\n255\n255 0 0  0 255 0\n0 0 255  10 20 30\n").unwrap();
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_bytes(
+        &image,
+        &[[255, 0, 0], [0, 255, 0], [0, 0, 255], [10, 20, 30]],
+    );
+}
+
+#[test]
+fn binary_image() {
+    let mut data = b"P6\n3 1 255\n".to_vec();
+    data.extend([1, 2, 3, 100, 150, 200, 255, 255, 255]);
+    let image = parse_ppm(&data).unwrap();
+    assert_eq!((image.width, image.height), (3, 1));
+    assert_bytes(&image, &[[1, 2, 3], [100, 150, 200], [255, 255, 255]]);
+}
+
+#[test]
+fn samples_are_rescaled_to_the_maximum_value() {
+    let image = parse_ppm(b"P3 1 1 15 15 0 8").unwrap();
+    // 8 of 15 rounds to 136 of 255
+    assert_bytes(&image, &[[255, 0, 136]]);
+
+    // Two big-endian bytes per sample above 255
+    let mut data = b"P6 1 1 65535\n".to_vec();
+    data.extend([0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+    assert_bytes(&parse_ppm(&data).unwrap(), &[[255, 0, 128]]);
+}
+
+#[test]
+fn samples_above_the_maximum_are_rejected() {
+    assert_format_error(parse_ppm(b"P3 1 1 15 16 0 0"));
+}
+
+#[test]
+fn invalid_maximum_value_is_rejected() {
+    assert_format_error(parse_ppm(b"P3 1 1 0 0 0 0"));
+    assert_format_error(parse_ppm(b"P3 1 1 65536 0 0 0"));
+}
+
+#[test]
+fn truncated_data_is_rejected() {
+    assert_format_error(parse_ppm(b"P3\n2 1\n255\n1 2 3 4 5\n"));
+    assert_format_error(parse_ppm(b"P6\n2 1\n255\n\x01\x02\x03\x04\x05"));
+    assert_format_error(parse_ppm(b"P6\n2 1\n"));
+}
+
+#[test]
+fn zero_size_is_rejected() {
+    assert_format_error(parse_ppm(b"P3\n0 0\n255\n"));
+    assert_format_error(parse_ppm(b"P6\n4 0\n255\n"));
+}
+
+#[test]
+fn huge_size_is_rejected() {
+    assert_format_error(parse_ppm(b"P6\n4294967295 4294967295\n255\n"));
+    assert_format_error(parse_ppm(b"P3\n4294967295 4294967295\n255\n"));
+    assert_format_error(parse_ppm(b"P6\n65536 65536\n255\n"));
+}
+
+#[test]
+fn unsupported_magic_is_rejected() {
+    assert_format_error(parse_ppm(b"P5\n1 1\n255\n\0"));
+    assert_format_error(parse_ppm(b""));
+}
+
+#[test]
+fn ascii_and_binary_writers_round_trip() {
+    let image = radiance();
+    let expected: Vec<[u8; 3]> = image.pixels().iter().map(|&p| bytes(p)).collect();
+    for format in [PpmFormat::Ascii, PpmFormat::Binary] {
+        let decoded = round_trip(format, ToneMapper::default(), &image);
+        assert_eq!((decoded.width, decoded.height), (3, 2));
+        assert_bytes(&decoded, &expected);
+    }
+}
+
+#[test]
+fn writer_applies_the_tone_mapping() {
+    let image = radiance();
+    let map = |f: fn(f64) -> f64| -> Vec<[u8; 3]> {
+        image
+            .pixels()
+            .iter()
+            .map(|p| bytes(Vec3::new(f(p.x), f(p.y), f(p.z))))
+            .collect()
+    };
+    for format in [PpmFormat::Ascii, PpmFormat::Binary] {
+        let reinhard = round_trip(format, ToneMapper::new(ToneMapping::Reinhard), &image);
+        assert_bytes(&reinhard, &map(|x| x / (1.0 + x)));
+
+        // One stop doubles the radiance before it is clamped
+        let mut exposure = ToneMapper::default();
+        exposure.set_exposure(1.0);
+        let exposed = round_trip(format, exposure, &image);
+        assert_bytes(&exposed, &map(|x| (2.0 * x).min(1.0)));
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use zharko::{
+    camera::{CameraBuilder, FisheyeMapping, Projection},
+    math::{
+        hittables::{HittableList, Sphere},
+        materials::Lambertian,
+        Ray, Vec3,
+    },
+};
+
+fn material() -> Arc<Lambertian> {
+    Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))
+}
+
+fn sphere(center: Vec3, radius: f64) -> HittableList {
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(center, radius, material())));
+    world
+}
+
+const LOOKFROM: Vec3 = Vec3 {
+    x: 1.0,
+    y: 2.0,
+    z: 3.0,
+};
+const LOOKAT: Vec3 = Vec3 {
+    x: 4.0,
+    y: 1.0,
+    z: -2.0,
+};
+
+/// A camera looking in an arbitrary direction
+fn camera(projection: Projection) -> CameraBuilder {
+    CameraBuilder::new()
+        .lookfrom(LOOKFROM)
+        .lookat(LOOKAT)
+        .samples_per_pixel(1)
+        .threads(1)
+        .projection(projection)
+}
+
+fn fisheye(mapping: FisheyeMapping) -> Projection {
+    Projection::Fisheye {
+        fov: 180.0,
+        mapping,
+    }
+}
+
+/// The direction of the ray through the center of every pixel. They all hit a sphere around the
+/// camera from the inside, where the normal points back at the camera.
+fn directions(camera: CameraBuilder) -> Vec<Option<Vec3>> {
+    let world = sphere(LOOKFROM, 10.0);
+    let buffers = camera.build().unwrap().render_aux_buffers(&world);
+    buffers
+        .depth
+        .iter()
+        .zip(buffers.normals)
+        .map(|(depth, normal)| depth.is_finite().then_some(-normal))
+        .collect()
+}
+
+/// Which pixels see the world
+fn coverage(camera: CameraBuilder, world: &HittableList) -> Vec<bool> {
+    let camera = camera.build().unwrap();
+    let depth = camera.render_aux_buffers(world).depth;
+    depth.iter().map(|d| d.is_finite()).collect()
+}
+
+#[test]
+fn orthographic_footprint_does_not_depend_on_the_distance() {
+    let near = sphere(Vec3::new(0.3, 0.0, -2.0), 0.5);
+    let far = sphere(Vec3::new(0.3, 0.0, -6.0), 0.5);
+    let camera = CameraBuilder::new()
+        .resolution(16, 16)
+        .projection(Projection::Orthographic { view_height: 2.0 });
+    let covered = coverage(camera.clone(), &near);
+    assert!(covered.iter().any(|&c| c) && covered.iter().any(|&c| !c));
+    assert_eq!(covered, coverage(camera.clone(), &far));
+
+    // In perspective the far sphere looks smaller
+    let camera = camera.projection(Projection::Perspective).vfov(60.0);
+    let pixels = |world| {
+        coverage(camera.clone(), world)
+            .into_iter()
+            .filter(|&c| c)
+            .count()
+    };
+    assert!(pixels(&far) < pixels(&near));
+}
+
+#[test]
+fn panoramas_look_along_the_view_direction_in_the_center() {
+    let forward = (LOOKAT - LOOKFROM).unit();
+    let projections = [
+        fisheye(FisheyeMapping::Equidistant),
+        fisheye(FisheyeMapping::Equisolid),
+        Projection::Equirectangular,
+    ];
+    for projection in projections {
+        // With an odd resolution the center of the image is the center of a pixel
+        let dirs = directions(camera(projection).resolution(9, 5));
+        let center = dirs[2 * 9 + 4].expect("a ray through the center");
+        assert!((center - forward).length() < 1e-9, "{:?}", projection);
+    }
+}
+
+#[test]
+fn pixels_outside_the_fisheye_circle_stay_black() {
+    for mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid] {
+        // The image circle is 8 pixels across, in the middle of the frame
+        let camera = camera(fisheye(mapping)).resolution(16, 8);
+        let dirs = directions(camera.clone());
+        for (x, y) in [(0, 0), (2, 4), (13, 4), (15, 7)] {
+            assert!(dirs[y * 16 + x].is_none(), "{} {}", x, y);
+        }
+        assert!(dirs[4 * 16 + 7].is_some());
+
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        let image = camera
+            .background(move |_: &Ray| white)
+            .build()
+            .unwrap()
+            .render(&HittableList::new());
+        for (x, y) in [(0, 0), (2, 4), (13, 4), (15, 7)] {
+            assert!(image.get_pixel(x, y).near_zero(), "{} {}", x, y);
+        }
+        assert!((*image.get_pixel(7, 4) - white).near_zero());
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use zharko::{
+    camera::{
+        CameraBuilder, CameraError, FisheyeMapping, LensSystem, Projection, StereoBuilder,
+        StereoCamera, StereoLayout,
+    },
+    math::{
+        hittables::{HittableList, Sphere, Triangle},
+        materials::Lambertian,
+        Ray, Vec3,
+    },
+};
+
+const LEFT: Vec3 = Vec3 {
+    x: 0.2,
+    y: 0.4,
+    z: 0.6,
+};
+const RIGHT: Vec3 = Vec3 {
+    x: 0.8,
+    y: 0.1,
+    z: 0.3,
+};
+
+fn build_error(builder: StereoBuilder) -> CameraError {
+    match builder.build() {
+        Err(e) => e,
+        Ok(_) => panic!("expected the stereo settings to be rejected"),
+    }
+}
+
+/// A camera at the origin looking down the negative `z` axis, where the background tells the
+/// eyes apart by the side the rays start on
+fn camera() -> CameraBuilder {
+    CameraBuilder::new()
+        .resolution(4, 2)
+        .samples_per_pixel(1)
+        .threads(1)
+        .background(|r: &Ray| if r.origin.x < 0.0 { LEFT } else { RIGHT })
+}
+
+fn assert_all(pixels: &[Vec3], expected: Vec3) {
+    for pixel in pixels {
+        assert!((*pixel - expected).length() < 1e-12, "{:?}", pixel);
+    }
+}
+
+fn material() -> Arc<Lambertian> {
+    Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))
+}
+
+/// A triangle facing the camera at the distance `z`
+fn triangle(z: f64) -> HittableList {
+    let mut world = HittableList::new();
+    world.add(Box::new(Triangle::new(
+        Vec3::new(-1.13, -0.77, -z),
+        Vec3::new(1.29, -0.93, -z),
+        Vec3::new(0.07, 1.21, -z),
+        material(),
+    )));
+    world
+}
+
+/// Which pixels of each eye see the world
+fn coverage(stereo: &StereoCamera, world: &HittableList) -> (Vec<bool>, Vec<bool>) {
+    let (left, right) = stereo.eyes();
+    let covered = |depth: Vec<f64>| depth.iter().map(|d| d.is_finite()).collect();
+    (
+        covered(left.render_aux_buffers(world).depth),
+        covered(right.render_aux_buffers(world).depth),
+    )
+}
+
+#[test]
+fn invalid_interocular_distance_is_rejected() {
+    assert_eq!(
+        build_error(StereoBuilder::new(camera()).interocular_distance(-0.1)),
+        CameraError::InvalidInterocularDistance(-0.1)
+    );
+    assert!(matches!(
+        build_error(StereoBuilder::new(camera()).interocular_distance(f64::NAN)),
+        CameraError::InvalidInterocularDistance(d) if d.is_nan()
+    ));
+}
+
+#[test]
+fn invalid_convergence_distance_is_rejected() {
+    for distance in [0.0, -2.0] {
+        assert_eq!(
+            build_error(StereoBuilder::new(camera()).convergence_distance(distance)),
+            CameraError::InvalidConvergenceDistance(distance)
+        );
+    }
+    assert!(matches!(
+        build_error(StereoBuilder::new(camera()).convergence_distance(f64::NAN)),
+        CameraError::InvalidConvergenceDistance(d) if d.is_nan()
+    ));
+    // Infinitely far away keeps the eyes parallel
+    assert!(StereoBuilder::new(camera())
+        .convergence_distance(f64::INFINITY)
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn unsupported_projections_are_rejected() {
+    let orthographic = Projection::Orthographic { view_height: 2.0 };
+    let fisheye = Projection::Fisheye {
+        fov: 180.0,
+        mapping: FisheyeMapping::Equisolid,
+    };
+    for projection in [orthographic, fisheye] {
+        assert_eq!(
+            build_error(StereoBuilder::new(camera().projection(projection))),
+            CameraError::UnsupportedStereoProjection(projection)
+        );
+    }
+}
+
+#[test]
+fn lens_systems_are_rejected() {
+    let lens = LensSystem::load(concat!(env!("CARGO_MANIFEST_DIR"), "/lenses/dgauss50.txt"))
+        .unwrap()
+        .scaled(0.001);
+    let camera = camera().lens(lens, 0.0433).focus_dist(2.0);
+    // The camera on its own is valid
+    assert!(camera.clone().build().is_ok());
+    assert_eq!(
+        build_error(StereoBuilder::new(camera)),
+        CameraError::UnsupportedStereoLens
+    );
+}
+
+#[test]
+fn side_by_side_puts_the_left_eye_on_the_left() {
+    let stereo = StereoBuilder::new(camera()).build().unwrap();
+    let frame = stereo.render(&HittableList::new());
+    assert_eq!((frame.width, frame.height), (8, 2));
+    for y in 0..2 {
+        for x in 0..8 {
+            let expected = if x < 4 { LEFT } else { RIGHT };
+            assert_all(&[*frame.get_pixel(x, y)], expected);
+        }
+    }
+}
+
+#[test]
+fn over_under_puts_the_left_eye_on_top() {
+    let stereo = StereoBuilder::new(camera())
+        .layout(StereoLayout::OverUnder)
+        .build()
+        .unwrap();
+    let frame = stereo.render(&HittableList::new());
+    assert_eq!((frame.width, frame.height), (4, 4));
+    assert_all(&frame.pixels()[..8], LEFT);
+    assert_all(&frame.pixels()[8..], RIGHT);
+}
+
+#[test]
+fn anaglyph_takes_red_from_the_left_eye() {
+    let stereo = StereoBuilder::new(camera())
+        .layout(StereoLayout::Anaglyph)
+        .build()
+        .unwrap();
+    let frame = stereo.render(&HittableList::new());
+    assert_eq!((frame.width, frame.height), (4, 2));
+    assert_all(frame.pixels(), Vec3::new(LEFT.x, RIGHT.y, RIGHT.z));
+}
+
+#[test]
+fn eyes_without_distance_see_the_same() {
+    let mut world = HittableList::new();
+    world.add(Box::new(Sphere::new(
+        Vec3::new(0.4, -0.2, -3.0),
+        1.0,
+        material(),
+    )));
+    let stereo = StereoBuilder::new(camera().resolution(16, 8))
+        .interocular_distance(0.0)
+        .convergence_distance(2.0)
+        .build()
+        .unwrap();
+    let (left, right) = stereo.eyes();
+    let (left, right) = (
+        left.render_aux_buffers(&world),
+        right.render_aux_buffers(&world),
+    );
+    assert!(left.depth.iter().any(|d| d.is_finite()));
+    assert_eq!(left.depth, right.depth);
+    for (l, r) in left.normals.iter().zip(&right.normals) {
+        assert_eq!((l.x, l.y, l.z), (r.x, r.y, r.z));
+    }
+}
+
+#[test]
+fn convergence_plane_has_no_parallax() {
+    let stereo = StereoBuilder::new(camera().resolution(32, 16))
+        .interocular_distance(2.0)
+        .convergence_distance(4.0)
+        .build()
+        .unwrap();
+
+    let world = triangle(4.0);
+    let (left, right) = coverage(&stereo, &world);
+    assert!(left.iter().any(|&c| c));
+    assert_eq!(left, right);
+    // Both eyes look parallel to each other, so the plane is 4 away from each of them
+    for depth in stereo.eyes().0.render_aux_buffers(&world).depth {
+        assert!(depth.is_infinite() || (depth - 4.0).abs() < 1e-9);
+    }
+
+    // Behind the plane the eyes disagree
+    let (left, right) = coverage(&stereo, &triangle(8.0));
+    assert_ne!(left, right);
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use zharko::math::{
+    textures::{Checker, SolidColor, Texture, UvChecker},
+    Vec3,
+};
+
+mod common;
+
+use common::assert_near;
+
+const EVEN: Vec3 = Vec3 {
+    x: 0.9,
+    y: 0.8,
+    z: 0.7,
+};
+const ODD: Vec3 = Vec3 {
+    x: 0.1,
+    y: 0.2,
+    z: 0.3,
+};
+
+#[test]
+fn solid_color_is_the_same_everywhere() {
+    let texture = SolidColor::new(EVEN);
+    assert_near(texture.value(0.0, 0.0, &Vec3::zero()), EVEN);
+    assert_near(texture.value(0.7, 0.2, &Vec3::new(-4.0, 9.0, 1.5)), EVEN);
+}
+
+#[test]
+fn checker_alternates_between_cubes() {
+    let checker = Checker::from_colors(2.0, EVEN, ODD);
+    let at = |x: f64, y: f64, z: f64| checker.value(0.0, 0.0, &Vec3::new(x, y, z));
+    // The cube at the origin and its diagonal neighbours
+    assert_near(at(0.5, 0.5, 0.5), EVEN);
+    assert_near(at(2.5, 2.5, 0.5), EVEN);
+    assert_near(at(2.5, 2.5, 2.5), ODD);
+    // Every step along an axis flips the parity
+    assert_near(at(2.5, 0.5, 0.5), ODD);
+    assert_near(at(0.5, 3.9, 0.5), ODD);
+    assert_near(at(0.5, 0.5, 5.0), EVEN);
+    // A cube starts at its lower corner
+    assert_near(at(1.99, 0.5, 0.5), EVEN);
+    assert_near(at(2.0, 0.5, 0.5), ODD);
+}
+
+#[test]
+fn checker_continues_below_zero() {
+    let checker = Checker::from_colors(2.0, EVEN, ODD);
+    let at = |x: f64, y: f64, z: f64| checker.value(0.0, 0.0, &Vec3::new(x, y, z));
+    // The cubes right below zero are neighbours of the one at the origin, not the same one
+    assert_near(at(-0.5, 0.5, 0.5), ODD);
+    assert_near(at(-0.5, -0.5, 0.5), EVEN);
+    assert_near(at(-0.5, -0.5, -0.5), ODD);
+    assert_near(at(-2.5, 0.5, 0.5), EVEN);
+}
+
+#[test]
+fn uv_checker_counts_squares() {
+    let checker = UvChecker::from_colors(4.0, 2.0, EVEN, ODD);
+    let at = |u: f64, v: f64| checker.value(u, v, &Vec3::zero());
+    assert_near(at(0.1, 0.1), EVEN);
+    assert_near(at(0.3, 0.1), ODD);
+    assert_near(at(0.3, 0.6), EVEN);
+    assert_near(at(0.1, 0.6), ODD);
+    assert_near(at(0.9, 0.9), EVEN);
+    // The point in space doesn't matter
+    assert_near(checker.value(0.3, 0.1, &Vec3::new(5.0, 1.0, 2.0)), ODD);
+}
+
+#[test]
+fn checkers_look_up_their_textures() {
+    // A checker of checkers: the fine pattern only shows up in the odd cubes
+    let fine = Arc::new(UvChecker::from_colors(2.0, 2.0, ODD, EVEN));
+    let checker = Checker::new(1.0, Arc::new(SolidColor::new(EVEN)), fine);
+    let p = Vec3::new(1.5, 0.5, 0.5);
+    assert_near(checker.value(0.25, 0.25, &p), ODD);
+    assert_near(checker.value(0.75, 0.25, &p), EVEN);
+    assert_near(checker.value(0.25, 0.25, &Vec3::new(0.5, 0.5, 0.5)), EVEN);
+}
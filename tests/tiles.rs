@@ -0,0 +1,73 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use zharko::{
+    camera::{CameraBuilder, Projection},
+    math::{hittables::HittableList, Ray, Vec3},
+    renderers::Image,
+};
+
+/// Not a multiple of the tile size in either direction, so the last row and column of tiles are
+/// partial
+const WIDTH: usize = 70;
+const HEIGHT: usize = 40;
+const SAMPLES: u16 = 4;
+
+/// Renders an empty world with an orthographic camera whose pixels are one unit wide. Every ray
+/// starts inside the pixel it samples, and the background turns that into the pixel's column and
+/// row, so the image doesn't depend on where in the pixel the samples land. Also returns how
+/// often the background was sampled.
+fn render(threads: usize) -> (Image, usize) {
+    let samples = Arc::new(AtomicUsize::new(0));
+    let counter = samples.clone();
+    let background = move |r: &Ray| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Vec3::new(
+            (r.origin.x + WIDTH as f64 / 2.0).floor(),
+            (HEIGHT as f64 / 2.0 - r.origin.y).floor(),
+            0.0,
+        )
+    };
+    let camera = CameraBuilder::new()
+        .resolution(WIDTH, HEIGHT)
+        .samples_per_pixel(SAMPLES)
+        .projection(Projection::Orthographic {
+            view_height: HEIGHT as f64,
+        })
+        .threads(threads)
+        .background(background)
+        .build()
+        .unwrap();
+    let image = camera.render(&HittableList::new());
+    let samples = samples.load(Ordering::Relaxed);
+    (image, samples)
+}
+
+#[test]
+fn every_pixel_is_rendered_once() {
+    let (image, samples) = render(3);
+    assert_eq!((image.width, image.height), (WIDTH, HEIGHT));
+    // Every pixel ended up in its own place
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let pixel = image.get_pixel(x, y);
+            assert_eq!((pixel.x, pixel.y), (x as f64, y as f64));
+        }
+    }
+    // No tile was rendered twice
+    assert_eq!(samples, WIDTH * HEIGHT * SAMPLES as usize);
+}
+
+#[test]
+fn thread_count_does_not_change_the_image() {
+    let (single, _) = render(1);
+    for threads in [2, 4, 16] {
+        let (multi, samples) = render(threads);
+        assert_eq!(samples, WIDTH * HEIGHT * SAMPLES as usize);
+        for (a, b) in single.pixels().iter().zip(multi.pixels()) {
+            assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z), "{} threads", threads);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use zharko::math::{
+    hittables::{HittableList, Triangle, TriangleMesh},
+    interval::Interval,
+    materials::Lambertian,
+    HitRecord, HitResult, Hittable, Ray, Vec3,
+};
+
+mod common;
+
+use common::assert_near;
+
+fn material() -> Arc<Lambertian> {
+    Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))
+}
+
+/// The right triangle with its corner at the origin, facing the positive `z` axis
+fn triangle() -> Triangle {
+    Triangle::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        material(),
+    )
+}
+
+fn hit(object: &dyn Hittable, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+    match object.hit(ray, ray_t) {
+        HitResult::Hit(rec) => Some(rec),
+        HitResult::NoHit => None,
+    }
+}
+
+/// The hit of a ray shot down the `z` axis through `(x, y)` from a distance of 1
+fn hit_from_above(object: &dyn Hittable, x: f64, y: f64) -> Option<HitRecord> {
+    let ray = Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0));
+    hit(object, &ray, Interval::new(0.0001, f64::INFINITY))
+}
+
+#[test]
+fn rays_hit_inside_the_edges() {
+    let triangle = triangle();
+    for (x, y) in [(0.01, 0.01), (0.98, 0.01), (0.01, 0.98), (0.49, 0.49)] {
+        let rec = hit_from_above(&triangle, x, y).expect("a hit inside the triangle");
+        assert_eq!(rec.t, 1.0);
+        assert_near(rec.point, Vec3::new(x, y, 0.0));
+        assert!(rec.front_face);
+        assert_near(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+        // Without texture coordinates `(u, v)` are the weights of the second and third vertex
+        assert!((rec.u - x).abs() < 1e-12 && (rec.v - y).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn rays_miss_outside_the_edges() {
+    let triangle = triangle();
+    for (x, y) in [
+        (-0.01, 0.5),
+        (0.5, -0.01),
+        (0.51, 0.5),
+        (0.5, 0.51),
+        (1.0, 1.0),
+    ] {
+        assert!(hit_from_above(&triangle, x, y).is_none(), "{} {}", x, y);
+    }
+}
+
+#[test]
+fn parallel_rays_miss() {
+    let triangle = triangle();
+    let ray_t = Interval::new(0.0001, f64::INFINITY);
+    // Above the plane and within it
+    for origin in [Vec3::new(-1.0, 0.2, 1.0), Vec3::new(-1.0, 0.2, 0.0)] {
+        let ray = Ray::new(origin, Vec3::new(1.0, 0.1, 0.0));
+        assert!(hit(&triangle, &ray, ray_t).is_none(), "{:?}", origin);
+    }
+}
+
+#[test]
+fn hits_outside_the_interval_are_ignored() {
+    let triangle = triangle();
+    let ray = Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0));
+    assert!(hit(&triangle, &ray, Interval::new(0.5, 1.5)).is_some());
+    assert!(hit(&triangle, &ray, Interval::new(0.0001, 0.5)).is_none());
+    assert!(hit(&triangle, &ray, Interval::new(1.5, f64::INFINITY)).is_none());
+    // The triangle is behind the ray
+    let away = Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::new(0.0, 0.0, 1.0));
+    assert!(hit(&triangle, &away, Interval::new(0.0001, f64::INFINITY)).is_none());
+}
+
+#[test]
+fn smooth_normal_is_interpolated() {
+    let normals = [
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(1.0, 0.0, 1.0),
+        Vec3::new(0.0, 1.0, 2.0),
+    ];
+    let triangle = Triangle::with_normals(
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ],
+        normals,
+        material(),
+    );
+    // The barycentric point with the weights 0.25, 0.25 and 0.5
+    let rec = hit_from_above(&triangle, 0.25, 0.5).unwrap();
+    let expected = 0.25 * normals[0].unit() + 0.25 * normals[1].unit() + 0.5 * normals[2].unit();
+    assert_near(rec.normal, expected.unit());
+}
+
+#[test]
+fn back_face_flips_the_normal() {
+    let below = Ray::new(Vec3::new(0.2, 0.3, -1.0), Vec3::new(0.0, 0.0, 1.0));
+    let ray_t = Interval::new(0.0001, f64::INFINITY);
+    let rec = hit(&triangle(), &below, ray_t).unwrap();
+    assert!(!rec.front_face);
+    assert_near(rec.normal, Vec3::new(0.0, 0.0, -1.0));
+
+    // Smooth normals are flipped the same way
+    let tilted = Vec3::new(1.0, 0.0, 1.0);
+    let smooth = Triangle::with_normals(
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ],
+        [tilted; 3],
+        material(),
+    );
+    let rec = hit(&smooth, &below, ray_t).unwrap();
+    assert!(!rec.front_face);
+    assert_near(rec.normal, -tilted.unit());
+}
+
+#[test]
+#[should_panic(expected = "Mesh index 3 out of bounds for 3 vertices")]
+fn mesh_index_out_of_range_panics() {
+    let vertices = vec![
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    ];
+    TriangleMesh::new(vertices, vec![[0, 1, 3]], material());
+}
+
+#[test]
+fn mesh_matches_list_of_triangles() {
+    // A bumpy 6x6 height field, split into two triangles per cell
+    const SIZE: usize = 6;
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut vertices = Vec::new();
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            vertices.push(Vec3::new(x as f64, y as f64, rng.random_range(-0.5..0.5)));
+        }
+    }
+    let mut indices = Vec::new();
+    for y in 0..SIZE - 1 {
+        for x in 0..SIZE - 1 {
+            let corner = y * SIZE + x;
+            indices.push([corner, corner + 1, corner + SIZE + 1]);
+            indices.push([corner, corner + SIZE + 1, corner + SIZE]);
+        }
+    }
+    let mut list = HittableList::new();
+    for face in &indices {
+        let [p0, p1, p2] = face.map(|i| vertices[i]);
+        list.add(Box::new(Triangle::new(p0, p1, p2, material())));
+    }
+    let mesh = TriangleMesh::new(vertices, indices, material());
+
+    let ray_t = Interval::new(0.0001, f64::INFINITY);
+    let mut hits = 0;
+    for _ in 0..2000 {
+        let origin = Vec3::new(
+            rng.random_range(-2.0..7.0),
+            rng.random_range(-2.0..7.0),
+            rng.random_range(-3.0..3.0),
+        );
+        let dir = Vec3::new(
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+        );
+        let ray = Ray::new(origin, dir);
+        match (hit(&list, &ray, ray_t), hit(&mesh, &ray, ray_t)) {
+            (None, None) => (),
+            (Some(expected), Some(actual)) => {
+                hits += 1;
+                assert_eq!(expected.t, actual.t);
+                assert_eq!(expected.front_face, actual.front_face);
+                assert_near(actual.normal, expected.normal);
+                assert_eq!((expected.u, expected.v), (actual.u, actual.v));
+            }
+            _ => panic!("mesh and list disagree on whether {:?} hits", ray.dir),
+        }
+    }
+    assert!(hits > 100, "{}", hits);
+}
@@ -0,0 +1,166 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use zharko::math::{
+    hittables::{Sphere, Triangle},
+    interval::Interval,
+    materials::Lambertian,
+    HitRecord, HitResult, Hittable, Ray, Vec3,
+};
+
+mod common;
+
+use common::{assert_near, assert_within};
+
+const CENTER: Vec3 = Vec3 {
+    x: 1.0,
+    y: -2.0,
+    z: 0.5,
+};
+const RADIUS: f64 = 2.0;
+
+fn material() -> Arc<Lambertian> {
+    Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)))
+}
+
+fn sphere() -> Sphere {
+    Sphere::new(CENTER, RADIUS, material())
+}
+
+/// The point of the sphere at `(u, v)`, following the parameterization documented on `Sphere`
+fn sphere_point(u: f64, v: f64) -> Vec3 {
+    let phi = 2.0 * PI * u - PI;
+    let theta = PI * v;
+    CENTER
+        + RADIUS
+            * Vec3::new(
+                theta.sin() * phi.cos(),
+                -theta.cos(),
+                -theta.sin() * phi.sin(),
+            )
+}
+
+/// The hit of a ray shot from `point + offset` straight at `point` on the surface of `object`
+fn hit_at(object: &dyn Hittable, point: Vec3, offset: Vec3) -> HitRecord {
+    let ray = Ray::new(point + offset, -offset);
+    match object.hit(&ray, Interval::new(0.0001, f64::INFINITY)) {
+        HitResult::Hit(rec) => rec,
+        HitResult::NoHit => panic!("expected a hit at {:?}", point),
+    }
+}
+
+fn assert_orthonormal(rec: &HitRecord) {
+    let (tangent, bitangent, normal) = rec.tangent_frame();
+    for axis in [tangent, bitangent, normal] {
+        assert!((axis.length() - 1.0).abs() < 1e-9, "{:?}", axis);
+    }
+    for (a, b) in [(tangent, bitangent), (bitangent, normal), (normal, tangent)] {
+        assert!(a.dot(&b).abs() < 1e-9, "{:?} {:?}", a, b);
+    }
+    // A right-handed frame around the shading normal
+    assert_near(normal, rec.normal);
+    assert_near(tangent.cross(&bitangent), normal);
+}
+
+#[test]
+fn sphere_uv_starts_at_negative_x() {
+    let sphere = sphere();
+    let cases = [
+        (Vec3::new(1.0, 0.0, 0.0), (0.5, 0.5)),
+        (Vec3::new(0.0, 0.0, 1.0), (0.25, 0.5)),
+        (Vec3::new(0.0, 0.0, -1.0), (0.75, 0.5)),
+        (Vec3::new(-1.0, 0.0, -1e-12), (1.0, 0.5)),
+        (Vec3::new(-1.0, -1.0, 1e-12).unit(), (0.0, 0.25)),
+    ];
+    for (dir, (u, v)) in cases {
+        let rec = hit_at(&sphere, CENTER + RADIUS * dir, dir);
+        assert!((rec.u - u).abs() < 1e-9, "u of {:?} is {}", dir, rec.u);
+        assert!((rec.v - v).abs() < 1e-9, "v of {:?} is {}", dir, rec.v);
+    }
+    // `v` runs from the bottom to the top pole
+    let top = Vec3::new(0.0, 1.0, 0.0);
+    assert!((hit_at(&sphere, CENTER + RADIUS * top, top).v - 1.0).abs() < 1e-9);
+    let bottom = Vec3::new(0.0, -1.0, 0.0);
+    assert!(hit_at(&sphere, CENTER + RADIUS * bottom, bottom).v.abs() < 1e-9);
+}
+
+#[test]
+fn sphere_derivatives_match_finite_differences() {
+    let sphere = sphere();
+    let h = 1e-6;
+    for (u, v) in [(0.1, 0.3), (0.4, 0.5), (0.65, 0.8), (0.9, 0.15)] {
+        let point = sphere_point(u, v);
+        let rec = hit_at(&sphere, point, point - CENTER);
+        assert!((rec.u - u).abs() < 1e-9 && (rec.v - v).abs() < 1e-9);
+
+        let dpdu = (sphere_point(u + h, v) - sphere_point(u - h, v)) / (2.0 * h);
+        let dpdv = (sphere_point(u, v + h) - sphere_point(u, v - h)) / (2.0 * h);
+        assert_within(rec.dpdu, dpdu, 1e-6);
+        assert_within(rec.dpdv, dpdv, 1e-6);
+    }
+}
+
+#[test]
+fn triangle_derivatives_match_finite_differences() {
+    let mut triangle = Triangle::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(2.0, 0.5, 0.0),
+        Vec3::new(0.5, 1.5, 1.0),
+        material(),
+    );
+    // Rotated and stretched in texture space
+    triangle.set_tex_coords([(0.2, 0.1), (0.9, 0.3), (0.1, 0.8)]);
+
+    let normal = Vec3::new(2.0, 0.5, 0.0).cross(&Vec3::new(0.5, 1.5, 1.0));
+    let point = |a: f64, b: f64| a * Vec3::new(2.0, 0.5, 0.0) + b * Vec3::new(0.5, 1.5, 1.0);
+    let center = hit_at(&triangle, point(0.3, 0.3), normal);
+    for (a, b) in [(0.31, 0.3), (0.3, 0.31), (0.29, 0.32)] {
+        let rec = hit_at(&triangle, point(a, b), normal);
+        // Texture coordinates are affine over the triangle, so this holds for any step
+        let predicted =
+            center.point + (rec.u - center.u) * center.dpdu + (rec.v - center.v) * center.dpdv;
+        assert_near(rec.point, predicted);
+        assert_near(rec.dpdu, center.dpdu);
+        assert_near(rec.dpdv, center.dpdv);
+    }
+}
+
+#[test]
+fn tangent_frame_is_orthonormal() {
+    let sphere = sphere();
+    for (u, v) in [(0.1, 0.3), (0.4, 0.5), (0.65, 0.8), (0.9, 0.15)] {
+        let point = sphere_point(u, v);
+        let rec = hit_at(&sphere, point, point - CENTER);
+        assert_orthonormal(&rec);
+        // The tangent follows increasing `u`
+        assert!(rec.tangent_frame().0.dot(&rec.dpdu) > 0.0);
+    }
+    // At the poles `dpdu` vanishes
+    let top = Vec3::new(0.0, 1.0, 0.0);
+    let rec = hit_at(&sphere, CENTER + RADIUS * top, top);
+    assert!(rec.dpdu.near_zero());
+    assert_orthonormal(&rec);
+
+    // From inside the normal is flipped, and the frame with it
+    let rec = hit_at(
+        &sphere,
+        sphere_point(0.4, 0.5),
+        CENTER - sphere_point(0.4, 0.5),
+    );
+    assert!(!rec.front_face);
+    assert_orthonormal(&rec);
+
+    // Degenerate texture coordinates still give a frame around the triangle normal
+    let mut triangle = Triangle::new(
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -1.0),
+        material(),
+    );
+    triangle.set_tex_coords([(0.5, 0.5); 3]);
+    let rec = hit_at(
+        &triangle,
+        Vec3::new(0.2, 0.0, -0.2),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    assert_orthonormal(&rec);
+}